@@ -0,0 +1,334 @@
+use std::{future::Future, marker::PhantomData};
+
+use anyhow::bail;
+use async_trait::async_trait;
+use twilight_interactions::command::CreateCommand;
+use twilight_model::{
+    application::{
+        command::{Command, CommandOptionChoice},
+        interaction::{
+            Interaction, application_command::CommandData,
+            message_component::MessageComponentInteractionData,
+        },
+    },
+    channel::message::{Embed, MessageFlags},
+    http::interaction::{InteractionResponse, InteractionResponseData, InteractionResponseType},
+};
+use twilight_util::builder::InteractionResponseDataBuilder;
+
+use crate::{Context, error::CommandError};
+
+/// A slash command with its handlers.
+///
+/// Components are routed to the command whose name prefixes their custom id,
+/// e.g. `queue:next:1` goes to the `queue` command.
+#[async_trait]
+pub trait BotCommand: CreateCommand + Send + Sync + 'static {
+    async fn handle(
+        interaction: Interaction,
+        data: CommandData,
+        ctx: &Context,
+    ) -> anyhow::Result<()>;
+
+    async fn autocomplete(
+        _interaction: Interaction,
+        _data: CommandData,
+        _ctx: &Context,
+    ) -> anyhow::Result<()> {
+        bail!("command {} has no autocomplete", Self::NAME)
+    }
+
+    async fn component(
+        _interaction: Interaction,
+        data: MessageComponentInteractionData,
+        _ctx: &Context,
+    ) -> anyhow::Result<()> {
+        bail!("command {} has no component {}", Self::NAME, data.custom_id)
+    }
+}
+
+#[async_trait]
+trait Entry: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    fn definition(&self) -> Command;
+
+    async fn handle(
+        &self,
+        interaction: Interaction,
+        data: CommandData,
+        ctx: &Context,
+    ) -> anyhow::Result<()>;
+
+    async fn autocomplete(
+        &self,
+        interaction: Interaction,
+        data: CommandData,
+        ctx: &Context,
+    ) -> anyhow::Result<()>;
+
+    async fn component(
+        &self,
+        interaction: Interaction,
+        data: MessageComponentInteractionData,
+        ctx: &Context,
+    ) -> anyhow::Result<()>;
+}
+
+struct Registered<T>(PhantomData<fn() -> T>);
+
+#[async_trait]
+impl<T: BotCommand> Entry for Registered<T> {
+    fn name(&self) -> &'static str {
+        T::NAME
+    }
+
+    fn definition(&self) -> Command {
+        T::create_command().into()
+    }
+
+    async fn handle(
+        &self,
+        interaction: Interaction,
+        data: CommandData,
+        ctx: &Context,
+    ) -> anyhow::Result<()> {
+        T::handle(interaction, data, ctx).await
+    }
+
+    async fn autocomplete(
+        &self,
+        interaction: Interaction,
+        data: CommandData,
+        ctx: &Context,
+    ) -> anyhow::Result<()> {
+        T::autocomplete(interaction, data, ctx).await
+    }
+
+    async fn component(
+        &self,
+        interaction: Interaction,
+        data: MessageComponentInteractionData,
+        ctx: &Context,
+    ) -> anyhow::Result<()> {
+        T::component(interaction, data, ctx).await
+    }
+}
+
+/// The list of commands known to the bot.
+#[derive(Default)]
+pub struct CommandRegistry {
+    commands: Vec<Box<dyn Entry>>,
+}
+
+impl CommandRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn register<T: BotCommand>(mut self) -> Self {
+        self.commands.push(Box::new(Registered::<T>(PhantomData)));
+        self
+    }
+
+    pub fn definitions(&self) -> Vec<Command> {
+        self.commands
+            .iter()
+            .map(|command| command.definition())
+            .collect()
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.get(name).is_ok()
+    }
+
+    pub async fn handle(
+        &self,
+        interaction: Interaction,
+        data: CommandData,
+        ctx: &Context,
+    ) -> anyhow::Result<()> {
+        self.get(&data.name)?.handle(interaction, data, ctx).await
+    }
+
+    pub async fn autocomplete(
+        &self,
+        interaction: Interaction,
+        data: CommandData,
+        ctx: &Context,
+    ) -> anyhow::Result<()> {
+        self.get(&data.name)?
+            .autocomplete(interaction, data, ctx)
+            .await
+    }
+
+    pub async fn component(
+        &self,
+        interaction: Interaction,
+        data: MessageComponentInteractionData,
+        ctx: &Context,
+    ) -> anyhow::Result<()> {
+        let name = data.custom_id.split(':').next().unwrap_or_default();
+        self.get(name)?.component(interaction, data, ctx).await
+    }
+
+    fn get(&self, name: &str) -> anyhow::Result<&dyn Entry> {
+        match self.commands.iter().find(|command| command.name() == name) {
+            Some(command) => Ok(command.as_ref()),
+            None => bail!("unknown command: {}", name),
+        }
+    }
+}
+
+/// Responds to `interaction` with a new message.
+pub async fn respond(
+    ctx: &Context,
+    interaction: &Interaction,
+    data: InteractionResponseData,
+) -> anyhow::Result<()> {
+    create_response(
+        ctx,
+        interaction,
+        InteractionResponseType::ChannelMessageWithSource,
+        Some(data),
+    )
+    .await
+}
+
+/// Responds to a component `interaction` by editing the message it is attached to.
+pub async fn update(
+    ctx: &Context,
+    interaction: &Interaction,
+    data: InteractionResponseData,
+) -> anyhow::Result<()> {
+    create_response(
+        ctx,
+        interaction,
+        InteractionResponseType::UpdateMessage,
+        Some(data),
+    )
+    .await
+}
+
+/// Answers an autocomplete `interaction` with up to 25 `choices`.
+pub async fn suggest(
+    ctx: &Context,
+    interaction: &Interaction,
+    choices: impl IntoIterator<Item = CommandOptionChoice>,
+) -> anyhow::Result<()> {
+    create_response(
+        ctx,
+        interaction,
+        InteractionResponseType::ApplicationCommandAutocompleteResult,
+        Some(
+            InteractionResponseDataBuilder::new()
+                .choices(choices.into_iter().take(25))
+                .build(),
+        ),
+    )
+    .await
+}
+
+/// Acknowledges a component `interaction` without changing the message it is attached to.
+pub async fn acknowledge(ctx: &Context, interaction: &Interaction) -> anyhow::Result<()> {
+    create_response(
+        ctx,
+        interaction,
+        InteractionResponseType::DeferredUpdateMessage,
+        None,
+    )
+    .await
+}
+
+/// Defers the response to `interaction` while `task` runs, then replaces it with the
+/// embed `task` produced, or reports the error it failed with.
+///
+/// The response is always finalized, so slow commands never leave the interaction hanging.
+/// Any components `task` attached to the response in the meantime are removed.
+pub async fn deferred(
+    ctx: &Context,
+    interaction: &Interaction,
+    task: impl Future<Output = anyhow::Result<Embed>>,
+) -> anyhow::Result<()> {
+    create_response(
+        ctx,
+        interaction,
+        InteractionResponseType::DeferredChannelMessageWithSource,
+        None,
+    )
+    .await?;
+
+    match task.await {
+        Ok(embed) => {
+            ctx.client
+                .interaction(interaction.application_id)
+                .update_response(&interaction.token)
+                .embeds(Some(&[embed]))
+                .components(Some(&[]))
+                .await?;
+        }
+        Err(error) => report_error(ctx, interaction, error, true).await?,
+    }
+
+    Ok(())
+}
+
+/// Tells the user who triggered `interaction` that it failed, in an ephemeral message.
+///
+/// Internal errors are logged with their details, which are never shown to the user.
+/// An `acknowledged` interaction has its deferred response replaced by a followup.
+pub async fn report_error(
+    ctx: &Context,
+    interaction: &Interaction,
+    error: anyhow::Error,
+    acknowledged: bool,
+) -> anyhow::Result<()> {
+    let error = CommandError::from(error);
+    match &error {
+        CommandError::Internal(error) => {
+            tracing::error!(?error, "error while handling interaction")
+        }
+        error => tracing::debug!(%error, "interaction failed"),
+    }
+
+    let client = ctx.client.interaction(interaction.application_id);
+    if acknowledged {
+        client.delete_response(&interaction.token).await?;
+        client
+            .create_followup(&interaction.token)
+            .flags(MessageFlags::EPHEMERAL)
+            .embeds(&[error.embed()])
+            .await?;
+    } else {
+        create_response(
+            ctx,
+            interaction,
+            InteractionResponseType::ChannelMessageWithSource,
+            Some(
+                InteractionResponseDataBuilder::new()
+                    .embeds([error.embed()])
+                    .flags(MessageFlags::EPHEMERAL)
+                    .build(),
+            ),
+        )
+        .await?;
+    }
+
+    Ok(())
+}
+
+async fn create_response(
+    ctx: &Context,
+    interaction: &Interaction,
+    kind: InteractionResponseType,
+    data: Option<InteractionResponseData>,
+) -> anyhow::Result<()> {
+    let response = InteractionResponse { kind, data };
+
+    ctx.client
+        .interaction(interaction.application_id)
+        .create_response(interaction.id, &interaction.token, &response)
+        .await?;
+
+    Ok(())
+}
@@ -0,0 +1,63 @@
+use std::{env, path::PathBuf, time::Duration};
+
+use anyhow::Context as _;
+
+/// What happens to the "Now playing" message while its track plays.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NowPlayingMode {
+    /// Post the message once and never touch it again.
+    Static,
+    /// Keep the progress bar up to date and mark the message finished when the track ends.
+    Live,
+    /// Keep the progress bar up to date and delete the message when the track ends.
+    Ephemeral,
+}
+
+pub struct Config {
+    pub now_playing: NowPlayingMode,
+    /// Directory `file:` queries are resolved against; local playback is disabled without it.
+    pub media_library: Option<PathBuf>,
+    /// Whether `/play` autocomplete runs yt-dlp searches, on top of the play history.
+    pub autocomplete_search: bool,
+    /// JSON file guild settings are persisted to.
+    pub settings_path: PathBuf,
+    /// How long the bot stays in a voice channel with nothing queued or nobody listening.
+    pub idle_timeout: Duration,
+}
+
+impl Config {
+    pub fn from_env() -> anyhow::Result<Self> {
+        let now_playing = match env::var("NOW_PLAYING").as_deref() {
+            Ok("live") => NowPlayingMode::Live,
+            Ok("ephemeral") => NowPlayingMode::Ephemeral,
+            Ok("static") | Err(_) => NowPlayingMode::Static,
+            Ok(mode) => anyhow::bail!("invalid NOW_PLAYING mode: {}", mode),
+        };
+
+        let media_library = env::var_os("MEDIA_LIBRARY").map(PathBuf::from);
+
+        let autocomplete_search =
+            matches!(env::var("AUTOCOMPLETE_SEARCH").as_deref(), Ok("1" | "true"));
+
+        let settings_path = env::var_os("SETTINGS_FILE")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from("settings.json"));
+
+        let idle_timeout = match env::var("IDLE_TIMEOUT") {
+            Ok(seconds) => Duration::from_secs(
+                seconds
+                    .parse()
+                    .with_context(|| format!("invalid IDLE_TIMEOUT: {}", seconds))?,
+            ),
+            Err(_) => Duration::from_secs(5 * 60),
+        };
+
+        Ok(Self {
+            now_playing,
+            media_library,
+            autocomplete_search,
+            settings_path,
+            idle_timeout,
+        })
+    }
+}
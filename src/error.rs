@@ -0,0 +1,72 @@
+use std::fmt;
+
+use twilight_mention::Mention;
+use twilight_model::{
+    channel::message::Embed,
+    id::{Id, marker::ChannelMarker},
+};
+use twilight_util::builder::embed::EmbedBuilder;
+
+/// Errors shown to the user who invoked a command.
+///
+/// Handlers return [`anyhow::Result`] and raise these with `bail!` or `?`; anything that
+/// is not a `CommandError` is reported as [`CommandError::Internal`].
+#[derive(Debug)]
+pub enum CommandError {
+    NotInVoice,
+    /// The user is elsewhere than the voice channel the bot plays in.
+    NotInBotChannel(Id<ChannelMarker>),
+    NotInGuild,
+    NothingPlaying,
+    SourceFailed(String),
+    InvalidArgument(String),
+    PermissionDenied(String),
+    Internal(anyhow::Error),
+}
+
+impl CommandError {
+    pub fn embed(&self) -> Embed {
+        EmbedBuilder::new()
+            .color(0xed4245)
+            .title("Error")
+            .description(self.to_string())
+            .build()
+    }
+}
+
+impl fmt::Display for CommandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CommandError::NotInVoice => f.write_str("You are not in a voice channel."),
+            CommandError::NotInBotChannel(channel_id) => {
+                write!(f, "Join {} to control the music.", channel_id.mention())
+            }
+            CommandError::NotInGuild => f.write_str("This command can only be used in a server."),
+            CommandError::NothingPlaying => f.write_str("Nothing is playing."),
+            CommandError::SourceFailed(query) => write!(f, "Could not play `{}`.", query),
+            CommandError::InvalidArgument(reason) => f.write_str(reason),
+            CommandError::PermissionDenied(reason) => f.write_str(reason),
+            CommandError::Internal(_) => {
+                f.write_str("Something went wrong while processing your request.")
+            }
+        }
+    }
+}
+
+impl std::error::Error for CommandError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            CommandError::Internal(error) => Some(error.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+impl From<anyhow::Error> for CommandError {
+    fn from(error: anyhow::Error) -> Self {
+        match error.downcast::<CommandError>() {
+            Ok(error) => error,
+            Err(error) => CommandError::Internal(error),
+        }
+    }
+}
@@ -1,33 +1,48 @@
 extern crate tracing;
 
 use std::{
-    collections::HashMap,
     env,
     sync::{
-        Arc, RwLock,
+        Arc,
         atomic::{AtomicBool, Ordering},
     },
 };
 
+use command::CommandRegistry;
+use config::Config;
 use dotenv::dotenv;
 use process::process_interactions;
-use songbird::{Songbird, shards::TwilightMap, tracks::TrackHandle};
+use settings::Settings;
+use songbird::{Songbird, shards::TwilightMap};
 use tracing::Level;
 use twilight_cache_inmemory::{InMemoryCache, InMemoryCacheBuilder, ResourceType};
 use twilight_gateway::{
     CloseFrame, ConfigBuilder, Event, EventTypeFlags, Intents, Shard, StreamExt, create_recommended,
 };
 use twilight_http::Client as HttpClient;
-use twilight_interactions::command::CreateCommand;
 use twilight_model::id::{Id, marker::GuildMarker};
 use twilight_standby::Standby;
 
+mod command;
+mod config;
+mod error;
 mod music;
+mod permissions;
 mod ping;
 mod process;
+mod settings;
 mod utils;
 
-use music::{PauseCommand, PlayCommand, ResumeCommand, SkipCommand, StopCommand};
+use music::{
+    ClearCommand, JoinCommand, LeaveCommand, LoopCommand, MoveCommand, NowPlayingCommand,
+    PauseCommand, PlayCommand, QueueCommand, RemoveCommand, ReplayCommand, ResumeCommand,
+    SearchCommand, SeekCommand, SettingsCommand, ShuffleCommand, SkipCommand, StopCommand,
+    VolumeCommand,
+    sources::{Sources, file::Library},
+    state::GuildStates,
+    suggestions::Suggestions,
+    track::TrackRegistry,
+};
 use ping::*;
 
 static SHUTDOWN: AtomicBool = AtomicBool::new(false);
@@ -35,12 +50,20 @@ static SHUTDOWN: AtomicBool = AtomicBool::new(false);
 pub type Context = Arc<ContextRef>;
 
 pub struct ContextRef {
+    pub config: Config,
+    pub commands: CommandRegistry,
     pub client: Arc<HttpClient>,
     pub cache: Arc<InMemoryCache>,
     pub http: reqwest::Client,
+    pub sources: Sources,
     pub standby: Standby,
     pub songbird: Songbird,
-    pub trackdata: RwLock<HashMap<Id<GuildMarker>, TrackHandle>>,
+    pub trackdata: TrackRegistry,
+    pub suggestions: Suggestions,
+    pub guilds: GuildStates,
+    pub settings: Settings,
+    /// Listing of the media library for autocomplete, if one is configured.
+    pub library: Option<Library>,
 }
 
 #[tokio::main]
@@ -59,14 +82,27 @@ async fn main() -> anyhow::Result<()> {
     let config = ConfigBuilder::new(token.clone(), Intents::all()).build();
 
     // Register global commands
-    let commands = [
-        PingCommand::create_command().into(),
-        PlayCommand::create_command().into(),
-        PauseCommand::create_command().into(),
-        ResumeCommand::create_command().into(),
-        SkipCommand::create_command().into(),
-        StopCommand::create_command().into(),
-    ];
+    let commands = CommandRegistry::new()
+        .register::<PingCommand>()
+        .register::<PlayCommand>()
+        .register::<SearchCommand>()
+        .register::<PauseCommand>()
+        .register::<ResumeCommand>()
+        .register::<SkipCommand>()
+        .register::<StopCommand>()
+        .register::<QueueCommand>()
+        .register::<NowPlayingCommand>()
+        .register::<LoopCommand>()
+        .register::<ShuffleCommand>()
+        .register::<RemoveCommand>()
+        .register::<MoveCommand>()
+        .register::<ClearCommand>()
+        .register::<SeekCommand>()
+        .register::<ReplayCommand>()
+        .register::<VolumeCommand>()
+        .register::<SettingsCommand>()
+        .register::<JoinCommand>()
+        .register::<LeaveCommand>();
     let application = http.current_user_application().await?.model().await?;
     let interaction_client = http.interaction(application.id);
 
@@ -80,7 +116,7 @@ async fn main() -> anyhow::Result<()> {
     if let Err(error) = interaction_client
         .set_guild_commands(
             Id::<GuildMarker>::new(env::var("SERVER_ID")?.parse()?),
-            &commands,
+            &commands.definitions(),
         )
         .await
     {
@@ -95,12 +131,26 @@ async fn main() -> anyhow::Result<()> {
     let mut senders = Vec::with_capacity(shards_len);
     let mut tasks = Vec::with_capacity(shards_len);
 
+    let bot_config = Config::from_env()?;
+    let reqwest_client = reqwest::Client::new();
+    let settings = Settings::load(bot_config.settings_path.clone()).await?;
+    let sources = Sources::with_defaults(reqwest_client.clone(), bot_config.media_library.clone());
+    let library = bot_config.media_library.clone().map(Library::new);
+
     let ctx = Arc::new(ContextRef {
+        config: bot_config,
+        commands,
         client: http.clone(),
-        http: reqwest::Client::new(),
+        http: reqwest_client,
+        sources,
         cache: Arc::new(
             InMemoryCacheBuilder::new()
-                .resource_types(ResourceType::MESSAGE | ResourceType::VOICE_STATE)
+                .resource_types(
+                    ResourceType::MESSAGE
+                        | ResourceType::VOICE_STATE
+                        | ResourceType::MEMBER
+                        | ResourceType::USER,
+                )
                 .build(),
         ),
         standby: Standby::new(),
@@ -114,6 +164,10 @@ async fn main() -> anyhow::Result<()> {
             http.current_user().await?.model().await?.id,
         ),
         trackdata: Default::default(),
+        suggestions: Default::default(),
+        guilds: Default::default(),
+        settings,
+        library,
     });
 
     for shard in shards {
@@ -153,9 +207,15 @@ async fn runner(mut shard: Shard, ctx: Context) {
 async fn handle_event(event: Event, ctx: Context) {
     ctx.cache.update(&event);
     ctx.songbird.process(&event).await;
+    ctx.standby.process(&event);
 
-    match event {
+    match &event {
         Event::GatewayClose(_) if SHUTDOWN.load(Ordering::Relaxed) => return,
+        Event::VoiceStateUpdate(update) => {
+            if let Some(guild_id) = update.guild_id {
+                tokio::spawn(music::idle::voice_state_update(ctx.clone(), guild_id));
+            }
+        }
         _ => {}
     }
 
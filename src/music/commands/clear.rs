@@ -0,0 +1,47 @@
+use async_trait::async_trait;
+use twilight_interactions::command::{CommandModel, CreateCommand};
+use twilight_mention::Mention;
+use twilight_model::application::interaction::{Interaction, application_command::CommandData};
+use twilight_util::builder::InteractionResponseDataBuilder;
+
+use crate::{
+    Context,
+    command::{BotCommand, respond},
+    error::CommandError,
+    music::controls,
+};
+
+#[derive(Debug, CommandModel, CreateCommand)]
+#[command(
+    name = "clear",
+    desc = "Remove all upcoming tracks, keeping the current one."
+)]
+pub struct ClearCommand;
+
+#[async_trait]
+impl BotCommand for ClearCommand {
+    async fn handle(
+        interaction: Interaction,
+        _data: CommandData,
+        ctx: &Context,
+    ) -> anyhow::Result<()> {
+        let guild_id = interaction.guild_id.ok_or(CommandError::NotInGuild)?;
+
+        tracing::debug!(
+            "clear command in channel {} by {}",
+            interaction.channel.clone().unwrap().id,
+            interaction.author().unwrap().mention()
+        );
+
+        let removed = controls::clear(ctx, guild_id).await?;
+
+        respond(
+            ctx,
+            &interaction,
+            InteractionResponseDataBuilder::new()
+                .content(format!("Cleared {} upcoming tracks", removed))
+                .build(),
+        )
+        .await
+    }
+}
@@ -0,0 +1,71 @@
+use anyhow::bail;
+use async_trait::async_trait;
+use twilight_interactions::command::{CommandModel, CreateCommand};
+use twilight_mention::Mention;
+use twilight_model::{
+    application::interaction::{Interaction, application_command::CommandData},
+    channel::message::Embed,
+    id::{Id, marker::ChannelMarker},
+};
+use twilight_util::builder::embed::EmbedBuilder;
+
+use crate::{
+    Context,
+    command::{BotCommand, deferred},
+    error::CommandError,
+    music::{player, voice},
+};
+
+#[derive(Debug, CommandModel, CreateCommand)]
+#[command(
+    name = "join",
+    desc = "Join your voice channel, or move the music to another one."
+)]
+pub struct JoinCommand {
+    #[command(
+        desc = "voice channel to join instead of yours",
+        channel_types = "guild_voice guild_stage_voice"
+    )]
+    pub channel: Option<Id<ChannelMarker>>,
+}
+
+#[async_trait]
+impl BotCommand for JoinCommand {
+    async fn handle(
+        interaction: Interaction,
+        data: CommandData,
+        ctx: &Context,
+    ) -> anyhow::Result<()> {
+        let command = JoinCommand::from_interaction(data.into())?;
+
+        tracing::debug!(
+            "join command in channel {} by {}",
+            interaction.channel.clone().unwrap().id,
+            interaction.author().unwrap().mention()
+        );
+
+        // Connecting can take longer than an interaction may go unanswered.
+        deferred(ctx, &interaction, command.join(&interaction, ctx)).await
+    }
+}
+
+impl JoinCommand {
+    async fn join(self, interaction: &Interaction, ctx: &Context) -> anyhow::Result<Embed> {
+        let guild_id = interaction.guild_id.ok_or(CommandError::NotInGuild)?;
+        let user_id = interaction.author().unwrap().id;
+
+        let Some(channel_id) = self
+            .channel
+            .or_else(|| voice::user_channel(ctx, guild_id, user_id))
+        else {
+            bail!(CommandError::NotInVoice);
+        };
+
+        player::connect(ctx, guild_id, channel_id).await?;
+
+        Ok(EmbedBuilder::new()
+            .color(0xf04628)
+            .description(format!("Joined {}", channel_id.mention()))
+            .build())
+    }
+}
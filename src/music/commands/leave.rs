@@ -0,0 +1,44 @@
+use async_trait::async_trait;
+use twilight_interactions::command::{CommandModel, CreateCommand};
+use twilight_mention::Mention;
+use twilight_model::application::interaction::{Interaction, application_command::CommandData};
+use twilight_util::builder::InteractionResponseDataBuilder;
+
+use crate::{
+    Context,
+    command::{BotCommand, respond},
+    error::CommandError,
+    music::controls,
+};
+
+#[derive(Debug, CommandModel, CreateCommand)]
+#[command(name = "leave", desc = "Stop the music and leave the voice channel.")]
+pub struct LeaveCommand;
+
+#[async_trait]
+impl BotCommand for LeaveCommand {
+    async fn handle(
+        interaction: Interaction,
+        _data: CommandData,
+        ctx: &Context,
+    ) -> anyhow::Result<()> {
+        let guild_id = interaction.guild_id.ok_or(CommandError::NotInGuild)?;
+
+        tracing::debug!(
+            "leave command in channel {} by {}",
+            interaction.channel.clone().unwrap().id,
+            interaction.author().unwrap().mention()
+        );
+
+        controls::leave(ctx, guild_id).await?;
+
+        respond(
+            ctx,
+            &interaction,
+            InteractionResponseDataBuilder::new()
+                .content("Left the voice channel")
+                .build(),
+        )
+        .await
+    }
+}
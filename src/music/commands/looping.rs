@@ -0,0 +1,54 @@
+use async_trait::async_trait;
+use twilight_interactions::command::{CommandModel, CreateCommand};
+use twilight_mention::Mention;
+use twilight_model::application::interaction::{Interaction, application_command::CommandData};
+use twilight_util::builder::InteractionResponseDataBuilder;
+
+use crate::{
+    Context,
+    command::{BotCommand, respond},
+    error::CommandError,
+    music::{controls, state::LoopMode},
+};
+
+#[derive(Debug, CommandModel, CreateCommand)]
+#[command(name = "loop", desc = "Repeat the current track or the whole queue.")]
+pub struct LoopCommand {
+    #[command(desc = "what to repeat")]
+    pub mode: LoopMode,
+}
+
+#[async_trait]
+impl BotCommand for LoopCommand {
+    async fn handle(
+        interaction: Interaction,
+        data: CommandData,
+        ctx: &Context,
+    ) -> anyhow::Result<()> {
+        let guild_id = interaction.guild_id.ok_or(CommandError::NotInGuild)?;
+        let command = LoopCommand::from_interaction(data.into())?;
+
+        tracing::debug!(
+            "loop command in channel {} by {}",
+            interaction.channel.clone().unwrap().id,
+            interaction.author().unwrap().mention()
+        );
+
+        controls::set_loop(ctx, guild_id, command.mode).await?;
+
+        let content = match command.mode {
+            LoopMode::Off => "Looping disabled",
+            LoopMode::Track => "Looping the current track",
+            LoopMode::Queue => "Looping the queue",
+        };
+
+        respond(
+            ctx,
+            &interaction,
+            InteractionResponseDataBuilder::new()
+                .content(content)
+                .build(),
+        )
+        .await
+    }
+}
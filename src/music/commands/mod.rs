@@ -1,11 +1,39 @@
+pub mod clear;
+pub mod join;
+pub mod leave;
+pub mod looping;
+pub mod moving;
+pub mod nowplaying;
 pub mod pause;
 pub mod play;
+pub mod queue;
+pub mod remove;
+pub mod replay;
 pub mod resume;
+pub mod search;
+pub mod seek;
+pub mod settings;
+pub mod shuffle;
 pub mod skip;
 pub mod stop;
+pub mod volume;
 
+pub use clear::ClearCommand;
+pub use join::JoinCommand;
+pub use leave::LeaveCommand;
+pub use looping::LoopCommand;
+pub use moving::MoveCommand;
+pub use nowplaying::NowPlayingCommand;
 pub use pause::PauseCommand;
 pub use play::PlayCommand;
+pub use queue::QueueCommand;
+pub use remove::RemoveCommand;
+pub use replay::ReplayCommand;
 pub use resume::ResumeCommand;
+pub use search::SearchCommand;
+pub use seek::SeekCommand;
+pub use settings::SettingsCommand;
+pub use shuffle::ShuffleCommand;
 pub use skip::SkipCommand;
 pub use stop::StopCommand;
+pub use volume::VolumeCommand;
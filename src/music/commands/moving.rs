@@ -0,0 +1,55 @@
+use async_trait::async_trait;
+use twilight_interactions::command::{CommandModel, CreateCommand};
+use twilight_mention::Mention;
+use twilight_model::application::interaction::{Interaction, application_command::CommandData};
+use twilight_util::builder::InteractionResponseDataBuilder;
+
+use crate::{
+    Context,
+    command::{BotCommand, respond},
+    error::CommandError,
+    music::{controls, embeds},
+};
+
+#[derive(Debug, CommandModel, CreateCommand)]
+#[command(name = "move", desc = "Move a track to another position in the queue.")]
+pub struct MoveCommand {
+    #[command(desc = "position of the track to move", min_value = 1)]
+    pub from: i64,
+    #[command(desc = "position to move it to", min_value = 1)]
+    pub to: i64,
+}
+
+#[async_trait]
+impl BotCommand for MoveCommand {
+    async fn handle(
+        interaction: Interaction,
+        data: CommandData,
+        ctx: &Context,
+    ) -> anyhow::Result<()> {
+        let guild_id = interaction.guild_id.ok_or(CommandError::NotInGuild)?;
+        let command = MoveCommand::from_interaction(data.into())?;
+
+        tracing::debug!(
+            "move command in channel {} by {}",
+            interaction.channel.clone().unwrap().id,
+            interaction.author().unwrap().mention()
+        );
+
+        let handle =
+            controls::move_track(ctx, guild_id, command.from as usize, command.to as usize).await?;
+        let title = match ctx.trackdata.get(guild_id, handle.uuid()) {
+            Some(data) => embeds::title(&data.metadata).to_owned(),
+            None => "Track".to_owned(),
+        };
+
+        respond(
+            ctx,
+            &interaction,
+            InteractionResponseDataBuilder::new()
+                .content(format!("Moved **{}** to position {}", title, command.to))
+                .build(),
+        )
+        .await
+    }
+}
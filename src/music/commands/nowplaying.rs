@@ -0,0 +1,72 @@
+use anyhow::bail;
+use async_trait::async_trait;
+use twilight_interactions::command::{CommandModel, CreateCommand};
+use twilight_mention::Mention;
+use twilight_model::application::interaction::{
+    Interaction, application_command::CommandData,
+    message_component::MessageComponentInteractionData,
+};
+use twilight_util::builder::InteractionResponseDataBuilder;
+
+use crate::{
+    Context,
+    command::{BotCommand, respond},
+    error::CommandError,
+    music::{controls, embeds},
+};
+
+#[derive(Debug, CommandModel, CreateCommand)]
+#[command(name = "nowplaying", desc = "Show the current track.")]
+pub struct NowPlayingCommand;
+
+#[async_trait]
+impl BotCommand for NowPlayingCommand {
+    async fn handle(
+        interaction: Interaction,
+        _data: CommandData,
+        ctx: &Context,
+    ) -> anyhow::Result<()> {
+        let guild_id = interaction.guild_id.ok_or(CommandError::NotInGuild)?;
+
+        tracing::debug!(
+            "nowplaying command in channel {} by {}",
+            interaction.channel.clone().unwrap().id,
+            interaction.author().unwrap().mention()
+        );
+
+        let current = match ctx.songbird.get(guild_id) {
+            Some(call_lock) => call_lock.lock().await.queue().current(),
+            None => None,
+        };
+
+        let data = current
+            .as_ref()
+            .and_then(|handle| ctx.trackdata.get(guild_id, handle.uuid()));
+
+        let (Some(handle), Some(data)) = (current, data) else {
+            bail!(CommandError::NothingPlaying);
+        };
+        let position = handle.get_info().await.ok().map(|state| state.position);
+
+        respond(
+            ctx,
+            &interaction,
+            InteractionResponseDataBuilder::new()
+                .embeds([
+                    embeds::now_playing(&data, position, ctx.guilds.get(guild_id).loop_mode)
+                        .build(),
+                ])
+                .build(),
+        )
+        .await
+    }
+
+    /// Handles the playback buttons attached to "Now playing" messages.
+    async fn component(
+        interaction: Interaction,
+        data: MessageComponentInteractionData,
+        ctx: &Context,
+    ) -> anyhow::Result<()> {
+        controls::handle_component(interaction, data, ctx).await
+    }
+}
@@ -1,184 +1,174 @@
-use std::process::Command;
-
 use anyhow::bail;
-use regex::Regex;
-use songbird::{
-    Event, TrackEvent,
-    input::{Compose, YoutubeDl},
-};
-use twilight_interactions::command::{CommandModel, CreateCommand};
+use async_trait::async_trait;
+use tokio::sync::mpsc;
+use twilight_interactions::command::{AutocompleteValue, CommandModel, CreateCommand};
 use twilight_mention::Mention;
 use twilight_model::{
-    application::interaction::{Interaction, application_command::CommandData},
-    http::interaction::{InteractionResponse, InteractionResponseType},
-    id::{Id, marker::UserMarker},
+    application::{
+        command::{CommandOptionChoice, CommandOptionChoiceValue},
+        interaction::{Interaction, application_command::CommandData},
+    },
+    channel::message::Embed,
 };
-use twilight_util::{
-    builder::{
-        InteractionResponseDataBuilder,
-        embed::{EmbedBuilder, ImageSource},
+use twilight_util::builder::embed::EmbedBuilder;
+
+use crate::{
+    Context,
+    command::{BotCommand, deferred, suggest},
+    error::CommandError,
+    music::{
+        embeds,
+        player::{enqueue, join},
+        sources::is_http_url,
+        suggestions::Suggestion,
     },
-    snowflake::Snowflake,
 };
 
-use crate::{Context, music::events::TrackPlayableHandler};
-
 #[derive(Debug, CommandModel, CreateCommand)]
 #[command(name = "play", desc = "Add a track to the queue.")]
 pub struct PlayCommand {
-    #[command(desc = "url or search term")]
-    pub query: String,
+    #[command(desc = "url or search term", autocomplete = true)]
+    pub query: Option<String>,
+    #[command(desc = "file from the media library", autocomplete = true)]
+    pub file: Option<String>,
 }
 
-impl PlayCommand {
-    pub async fn handle(
+#[derive(Debug, CommandModel)]
+#[command(autocomplete = true)]
+pub struct PlayAutocomplete {
+    pub query: AutocompleteValue<String>,
+    pub file: AutocompleteValue<String>,
+}
+
+#[async_trait]
+impl BotCommand for PlayCommand {
+    async fn handle(
         interaction: Interaction,
         data: CommandData,
         ctx: &Context,
     ) -> anyhow::Result<()> {
-        let client = ctx.client.interaction(interaction.application_id);
-        let guild_id = interaction.guild_id.unwrap();
         let command = PlayCommand::from_interaction(data.into())?;
 
-        let response = InteractionResponse {
-            kind: InteractionResponseType::ChannelMessageWithSource,
-            data: Some(
-                InteractionResponseDataBuilder::new()
-                    .content("Processing")
-                    .build(),
+        deferred(ctx, &interaction, command.play(&interaction, ctx)).await
+    }
+
+    async fn autocomplete(
+        interaction: Interaction,
+        data: CommandData,
+        ctx: &Context,
+    ) -> anyhow::Result<()> {
+        let command = PlayAutocomplete::from_interaction(data.into())?;
+
+        // Choice values are limited to 100 characters, so longer ones can only be typed out.
+        let choices: Vec<_> = match (command.query, command.file) {
+            (AutocompleteValue::Focused(input), _) => query_suggestions(&interaction, ctx, &input)
+                .await
+                .into_iter()
+                .filter(|suggestion| suggestion.query.chars().count() <= 100)
+                .map(|suggestion| choice(suggestion.name(), suggestion.query))
+                .collect(),
+            (_, AutocompleteValue::Focused(filter)) => match &ctx.library {
+                Some(library) => library
+                    .search(&filter, 50)
+                    .await?
+                    .into_iter()
+                    .filter(|path| path.chars().count() <= 100)
+                    .map(|path| choice(path.clone(), path))
+                    .collect(),
+                None => Vec::new(),
+            },
+            _ => Vec::new(),
+        };
+
+        suggest(ctx, &interaction, choices).await
+    }
+}
+
+/// Suggests recently played queries, followed by search results if enabled.
+async fn query_suggestions(
+    interaction: &Interaction,
+    ctx: &Context,
+    input: &str,
+) -> Vec<Suggestion> {
+    let Some(guild_id) = interaction.guild_id else {
+        return Vec::new();
+    };
+
+    let mut suggestions = ctx.suggestions.history(guild_id, input);
+    if ctx.config.autocomplete_search && input.trim().chars().count() >= 3 && !is_http_url(input) {
+        let user_id = interaction.author().unwrap().id;
+        suggestions.extend(ctx.suggestions.search(user_id, input).await);
+    }
+
+    suggestions
+}
+
+fn choice(name: String, value: String) -> CommandOptionChoice {
+    CommandOptionChoice {
+        name,
+        name_localizations: None,
+        value: CommandOptionChoiceValue::String(value),
+    }
+}
+
+impl PlayCommand {
+    async fn play(self, interaction: &Interaction, ctx: &Context) -> anyhow::Result<Embed> {
+        let guild_id = interaction.guild_id.ok_or(CommandError::NotInGuild)?;
+        let requester = interaction.author().unwrap().id;
+
+        let query = match (self.query, self.file) {
+            (_, Some(_)) if ctx.config.media_library.is_none() => bail!(
+                CommandError::InvalidArgument("Local files are not enabled.".to_owned())
             ),
+            (_, Some(path)) => format!("file:{}", path),
+            (Some(query), None) => query,
+            (None, None) => bail!(CommandError::InvalidArgument(
+                "Give a `query` or a `file` to play.".to_owned()
+            )),
         };
-        client
-            .create_response(interaction.id, &interaction.token, &response)
-            .await?;
-
-        // Join voice channel
-        if ctx
-            .cache
-            .voice_state(
-                Id::<UserMarker>::new(interaction.application_id.id()),
-                guild_id,
-            )
-            .is_none()
-        {
-            let Some(voice_state) = ctx
-                .cache
-                .voice_state(interaction.author_id().unwrap(), guild_id)
-            else {
-                tracing::error!("You are not in a voice channel {}", guild_id);
-
-                client
-                    .update_response(&interaction.token)
-                    .content(Some("You are not in a voice channel"))
-                    .await?;
-
-                bail!("You are not in a voice channel");
-            };
-
-            let channel_id = voice_state.channel_id();
-
-            tracing::debug!("joining voice channel {} in guild {}", channel_id, guild_id);
-
-            match ctx.songbird.join(guild_id, channel_id).await {
-                Ok(_) => {}
-                Err(error) => {
-                    tracing::error!(?error, "join voice channel");
-                    client
-                        .update_response(&interaction.token)
-                        .content(Some("Failed to join voice channel"))
-                        .await?;
-
-                    bail!(error);
-                }
-            }
+
+        join(ctx, interaction).await?;
+
+        let Some(resolver) = ctx.sources.resolver(&query).await else {
+            bail!(CommandError::SourceFailed(query));
         };
 
-        let mut to_queue = Vec::<YoutubeDl>::new();
-        if !command.query.starts_with("http") {
-            to_queue.push(YoutubeDl::new_search(
-                ctx.http.clone(),
-                command.query.clone(),
-            ));
-        } else if command.query.contains("playlist") {
-            let output = Command::new("yt-dlp")
-                .args(["-j", "--flat-playlist", &command.query])
-                .output();
-
-            let raw_list = match output {
-                Ok(list) => String::from_utf8(list.stdout).unwrap(),
-                Err(e) => bail!("yt-dlp error {}", e),
-            };
-
-            let re = Regex::new(r#""url": "(https://www.youtube.com/watch\?v=[A-Za-z0-9]{11})""#)
-                .unwrap();
-
-            let urls: Vec<String> = re
-                .captures_iter(&raw_list)
-                .map(|cap| cap[1].to_string())
-                .collect();
-
-            for url in urls {
-                to_queue.push(YoutubeDl::new(ctx.http.clone(), url));
+        let channel_id = interaction.channel.as_ref().unwrap().id;
+
+        // Tracks are queued as the resolver produces them, so playback of a playlist
+        // starts before the whole of it is resolved. The receiver is moved in, so a failed
+        // enqueue closes the channel and stops the resolver instead of blocking it.
+        let (sender, mut tracks) = mpsc::channel(16);
+        let (resolved, queued) = tokio::join!(resolver.resolve(&query, sender), async move {
+            let mut queued = Vec::new();
+            while let Some(track) = tracks.recv().await {
+                queued.push(enqueue(ctx, guild_id, requester, channel_id, track).await?);
             }
-        } else {
-            to_queue.push(YoutubeDl::new(ctx.http.clone(), command.query));
+            anyhow::Ok(queued)
+        });
+        let queued = queued?;
+
+        if let Err(error) = resolved {
+            tracing::warn!(?error, "failed to resolve {}", query);
         }
 
-        for src in to_queue.iter_mut() {
-            let Ok(metadata) = src.aux_metadata().await else {
-                client
-                    .update_response(&interaction.token)
-                    .content(Some("Error processing your request"))
-                    .await?;
-                continue;
-            };
-
-            let Some(call_lock) = ctx.songbird.get(guild_id) else {
-                tracing::error!("Bami is not in a voice channel");
-                bail!("Bami is not in a voice channel");
-            };
-
-            let track;
-            {
-                let mut call = call_lock.lock().await;
-                track = call.enqueue_input(src.clone().into()).await;
+        match queued.as_slice() {
+            [] => bail!(CommandError::SourceFailed(query)),
+            [metadata] => {
+                // Searches are remembered by the URL they resolved to.
+                let replay = metadata.source_url.as_deref().unwrap_or(&query);
+                ctx.suggestions
+                    .record(guild_id, embeds::title(metadata), replay);
+                Ok(embeds::queued(metadata, requester).build())
             }
-
-            ctx.client
-                .create_message(interaction.channel.as_ref().unwrap().id)
-                .embeds(&[EmbedBuilder::new()
+            tracks => {
+                ctx.suggestions.record(guild_id, &query, &query);
+                Ok(EmbedBuilder::new()
                     .color(0xf04628)
-                    .title(metadata.title.as_ref().unwrap())
-                    .url(metadata.source_url.as_ref().unwrap())
-                    .thumbnail(ImageSource::url(metadata.thumbnail.as_ref().unwrap()).unwrap())
-                    .description(format!(
-                        "Requested by {}",
-                        interaction.author().unwrap().mention(),
-                    ))
-                    .build()])
-                .await
-                .unwrap();
-
-            track
-                .add_event(
-                    Event::Track(TrackEvent::Playable),
-                    TrackPlayableHandler {
-                        channel_id: interaction.channel.as_ref().unwrap().id,
-                        metadata: metadata.clone(),
-                        user: interaction.author().unwrap().id,
-                        ctx: ctx.clone(),
-                    },
-                )
-                .unwrap();
-
-            tracing::info!("Queued track {}", &metadata.title.unwrap())
+                    .title(format!("Queued {} tracks", tracks.len()))
+                    .description(format!("Requested by {}", requester.mention()))
+                    .build())
+            }
         }
-
-        client
-            .update_response(&interaction.token)
-            .content(Some(format!("Qeueued {} songs", to_queue.len()).as_str()))
-            .await?;
-
-        Ok(())
     }
 }
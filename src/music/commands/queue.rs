@@ -0,0 +1,208 @@
+use std::time::{Duration, UNIX_EPOCH};
+
+use async_trait::async_trait;
+use songbird::tracks::TrackHandle;
+use twilight_interactions::command::{CommandModel, CreateCommand};
+use twilight_mention::{
+    Mention,
+    timestamp::{Timestamp, TimestampStyle},
+};
+use twilight_model::{
+    application::interaction::{
+        Interaction, application_command::CommandData,
+        message_component::MessageComponentInteractionData,
+    },
+    channel::message::{
+        Component, Embed,
+        component::{ActionRow, Button, ButtonStyle},
+    },
+    id::{Id, marker::GuildMarker},
+};
+use twilight_util::builder::{
+    InteractionResponseDataBuilder,
+    embed::{EmbedBuilder, EmbedFooterBuilder},
+};
+
+use crate::{
+    Context,
+    command::{BotCommand, respond, update},
+    error::CommandError,
+    music::embeds,
+    utils::to_timestamp,
+};
+
+const PAGE_SIZE: usize = 10;
+
+#[derive(Debug, CommandModel, CreateCommand)]
+#[command(name = "queue", desc = "Show the tracks in the queue.")]
+pub struct QueueCommand;
+
+#[async_trait]
+impl BotCommand for QueueCommand {
+    async fn handle(
+        interaction: Interaction,
+        _data: CommandData,
+        ctx: &Context,
+    ) -> anyhow::Result<()> {
+        let guild_id = interaction.guild_id.ok_or(CommandError::NotInGuild)?;
+
+        tracing::debug!(
+            "queue command in channel {} by {}",
+            interaction.channel.clone().unwrap().id,
+            interaction.author().unwrap().mention()
+        );
+
+        let (embed, components) = render(ctx, guild_id, 0).await;
+
+        respond(
+            ctx,
+            &interaction,
+            InteractionResponseDataBuilder::new()
+                .embeds([embed])
+                .components(components)
+                .build(),
+        )
+        .await
+    }
+
+    /// Handles the Previous/Next buttons, whose custom id carries the page to show.
+    async fn component(
+        interaction: Interaction,
+        data: MessageComponentInteractionData,
+        ctx: &Context,
+    ) -> anyhow::Result<()> {
+        let guild_id = interaction.guild_id.ok_or(CommandError::NotInGuild)?;
+
+        let page = data
+            .custom_id
+            .rsplit(':')
+            .next()
+            .and_then(|page| page.parse().ok())
+            .unwrap_or(0);
+
+        let (embed, components) = render(ctx, guild_id, page).await;
+
+        update(
+            ctx,
+            &interaction,
+            InteractionResponseDataBuilder::new()
+                .embeds([embed])
+                .components(components)
+                .build(),
+        )
+        .await
+    }
+}
+
+async fn render(ctx: &Context, guild_id: Id<GuildMarker>, page: usize) -> (Embed, Vec<Component>) {
+    let tracks = match ctx.songbird.get(guild_id) {
+        Some(call_lock) => call_lock.lock().await.queue().current_queue(),
+        None => Vec::new(),
+    };
+
+    let embed = EmbedBuilder::new().color(0xf04628).title("Queue");
+
+    let Some((current, upcoming)) = tracks.split_first() else {
+        return (embed.description("The queue is empty").build(), Vec::new());
+    };
+
+    let pages = upcoming.len().div_ceil(PAGE_SIZE).max(1);
+    let page = page.min(pages - 1);
+
+    let position = current
+        .get_info()
+        .await
+        .map(|state| state.position)
+        .unwrap_or_default();
+    let remaining = tracks
+        .iter()
+        .filter_map(|track| ctx.trackdata.get(guild_id, track.uuid()))
+        .filter_map(|data| data.metadata.duration)
+        .sum::<Duration>()
+        .saturating_sub(position);
+
+    let mut description = format!("**Now playing**\n{}\n", describe(ctx, guild_id, current));
+    if !upcoming.is_empty() {
+        description.push_str("\n**Up next**\n");
+    }
+    for (index, track) in upcoming
+        .iter()
+        .enumerate()
+        .skip(page * PAGE_SIZE)
+        .take(PAGE_SIZE)
+    {
+        description.push_str(&format!(
+            "`{}.` {}\n",
+            index + 1,
+            describe(ctx, guild_id, track)
+        ));
+    }
+
+    let embed = embed
+        .description(description)
+        .footer(EmbedFooterBuilder::new(format!(
+            "Page {}/{} • {} upcoming • {} remaining • Volume {}%",
+            page + 1,
+            pages,
+            upcoming.len(),
+            to_timestamp(remaining.as_secs()),
+            ctx.settings.get(guild_id).volume
+        )))
+        .build();
+
+    if pages == 1 {
+        return (embed, Vec::new());
+    }
+
+    let components = vec![Component::ActionRow(ActionRow {
+        components: vec![
+            page_button(
+                "Previous",
+                format!("queue:prev:{}", page.saturating_sub(1)),
+                page == 0,
+            ),
+            page_button(
+                "Next",
+                format!("queue:next:{}", page + 1),
+                page + 1 == pages,
+            ),
+        ],
+    })];
+
+    (embed, components)
+}
+
+fn describe(ctx: &Context, guild_id: Id<GuildMarker>, track: &TrackHandle) -> String {
+    let Some(data) = ctx.trackdata.get(guild_id, track.uuid()) else {
+        return "Unknown track".to_owned();
+    };
+
+    let queued_at = data
+        .queued_at
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs());
+    let mut description = format!(
+        "{} `{}` {} queued {}",
+        embeds::song(&data.metadata),
+        embeds::duration(&data.metadata),
+        data.requester.mention(),
+        Timestamp::new(queued_at, Some(TimestampStyle::RelativeTime)).mention()
+    );
+    if let Some(volume) = *data.volume.read().unwrap() {
+        description.push_str(&format!(" 🔊 {}%", volume));
+    }
+
+    description
+}
+
+fn page_button(label: &str, custom_id: String, disabled: bool) -> Component {
+    Component::Button(Button {
+        custom_id: Some(custom_id),
+        disabled,
+        emoji: None,
+        label: Some(label.to_owned()),
+        style: ButtonStyle::Secondary,
+        url: None,
+        sku_id: None,
+    })
+}
@@ -0,0 +1,89 @@
+use async_trait::async_trait;
+use twilight_interactions::command::{CommandModel, CreateCommand};
+use twilight_mention::Mention;
+use twilight_model::{
+    application::interaction::{Interaction, application_command::CommandData},
+    id::{Id, marker::UserMarker},
+};
+use twilight_util::builder::InteractionResponseDataBuilder;
+
+use crate::{
+    Context,
+    command::{BotCommand, respond},
+    error::CommandError,
+    music::controls,
+};
+
+#[derive(Debug, CommandModel, CreateCommand)]
+#[command(name = "remove", desc = "Remove tracks from the queue.")]
+pub enum RemoveCommand {
+    #[command(name = "track")]
+    Track(RemoveTrack),
+    #[command(name = "user")]
+    User(RemoveUser),
+}
+
+#[derive(Debug, CommandModel, CreateCommand)]
+#[command(
+    name = "track",
+    desc = "Remove a track, or a range of tracks, by position."
+)]
+pub struct RemoveTrack {
+    #[command(desc = "position of the track, as shown by /queue", min_value = 1)]
+    pub position: i64,
+    #[command(desc = "last position to remove, to remove a range", min_value = 1)]
+    pub to: Option<i64>,
+}
+
+#[derive(Debug, CommandModel, CreateCommand)]
+#[command(
+    name = "user",
+    desc = "Remove every upcoming track requested by a user."
+)]
+pub struct RemoveUser {
+    #[command(desc = "whose tracks to remove")]
+    pub user: Id<UserMarker>,
+}
+
+#[async_trait]
+impl BotCommand for RemoveCommand {
+    async fn handle(
+        interaction: Interaction,
+        data: CommandData,
+        ctx: &Context,
+    ) -> anyhow::Result<()> {
+        let guild_id = interaction.guild_id.ok_or(CommandError::NotInGuild)?;
+        let command = RemoveCommand::from_interaction(data.into())?;
+
+        tracing::debug!(
+            "remove command in channel {} by {}",
+            interaction.channel.clone().unwrap().id,
+            interaction.author().unwrap().mention()
+        );
+
+        let content = match command {
+            RemoveCommand::Track(RemoveTrack { position, to }) => {
+                let to = to.unwrap_or(position);
+                let (from, to) = (position.min(to) as usize, position.max(to) as usize);
+                let removed = controls::remove(ctx, guild_id, from, to).await?;
+                match removed {
+                    1 => format!("Removed the track at position {}", from),
+                    removed => format!("Removed {} tracks", removed),
+                }
+            }
+            RemoveCommand::User(RemoveUser { user }) => {
+                let removed = controls::remove_by(ctx, guild_id, user).await?;
+                format!("Removed {} tracks requested by {}", removed, user.mention())
+            }
+        };
+
+        respond(
+            ctx,
+            &interaction,
+            InteractionResponseDataBuilder::new()
+                .content(content)
+                .build(),
+        )
+        .await
+    }
+}
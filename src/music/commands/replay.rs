@@ -0,0 +1,45 @@
+use async_trait::async_trait;
+use twilight_interactions::command::{CommandModel, CreateCommand};
+use twilight_mention::Mention;
+use twilight_model::application::interaction::{Interaction, application_command::CommandData};
+use twilight_util::builder::InteractionResponseDataBuilder;
+
+use crate::{
+    Context,
+    command::{BotCommand, respond},
+    error::CommandError,
+    music::controls,
+    utils::Seek,
+};
+
+#[derive(Debug, CommandModel, CreateCommand)]
+#[command(name = "replay", desc = "Restart the current track.")]
+pub struct ReplayCommand;
+
+#[async_trait]
+impl BotCommand for ReplayCommand {
+    async fn handle(
+        interaction: Interaction,
+        _data: CommandData,
+        ctx: &Context,
+    ) -> anyhow::Result<()> {
+        let guild_id = interaction.guild_id.ok_or(CommandError::NotInGuild)?;
+
+        tracing::debug!(
+            "replay command in channel {} by {}",
+            interaction.channel.clone().unwrap().id,
+            interaction.author().unwrap().mention()
+        );
+
+        controls::seek(ctx, guild_id, Seek::To(0)).await?;
+
+        respond(
+            ctx,
+            &interaction,
+            InteractionResponseDataBuilder::new()
+                .content("Replaying")
+                .build(),
+        )
+        .await
+    }
+}
@@ -1,25 +1,28 @@
+use async_trait::async_trait;
 use twilight_interactions::command::{CommandModel, CreateCommand};
 use twilight_mention::Mention;
-use twilight_model::{
-    application::interaction::{Interaction, application_command::CommandData},
-    http::interaction::{InteractionResponse, InteractionResponseType},
-};
+use twilight_model::application::interaction::{Interaction, application_command::CommandData};
 use twilight_util::builder::InteractionResponseDataBuilder;
 
-use crate::Context;
+use crate::{
+    Context,
+    command::{BotCommand, respond},
+    error::CommandError,
+    music::controls,
+};
 
 #[derive(Debug, CommandModel, CreateCommand)]
 #[command(name = "resume", desc = "Resume the current track.")]
 pub struct ResumeCommand;
 
-impl ResumeCommand {
-    pub async fn handle(
+#[async_trait]
+impl BotCommand for ResumeCommand {
+    async fn handle(
         interaction: Interaction,
         _data: CommandData,
         ctx: &Context,
     ) -> anyhow::Result<()> {
-        let client = ctx.client.interaction(interaction.application_id);
-        let guild_id = interaction.guild_id.unwrap();
+        let guild_id = interaction.guild_id.ok_or(CommandError::NotInGuild)?;
 
         tracing::debug!(
             "resume command in channel {} by {}",
@@ -27,26 +30,15 @@ impl ResumeCommand {
             interaction.author().unwrap().mention()
         );
 
-        if let Some(call_lock) = ctx.songbird.get(guild_id) {
-            let call = call_lock.lock().await;
-            call.queue().resume()?;
-        }
-
-        let response = InteractionResponse {
-            kind: InteractionResponseType::ChannelMessageWithSource,
-            data: Some(
-                InteractionResponseDataBuilder::new()
-                    .content("Resuming")
-                    .build(),
-            ),
-        };
-
-        client
-            .create_response(interaction.id, &interaction.token, &response)
-            .await?;
-
-        // TODO: Resume
+        controls::resume(ctx, guild_id).await?;
 
-        Ok(())
+        respond(
+            ctx,
+            &interaction,
+            InteractionResponseDataBuilder::new()
+                .content("Resuming")
+                .build(),
+        )
+        .await
     }
 }
@@ -0,0 +1,213 @@
+use std::time::Duration;
+
+use anyhow::bail;
+use async_trait::async_trait;
+use twilight_interactions::command::{CommandModel, CreateCommand};
+use twilight_mention::Mention;
+use twilight_model::{
+    application::interaction::{
+        Interaction, InteractionData, application_command::CommandData,
+        message_component::MessageComponentInteractionData,
+    },
+    channel::message::{
+        Component, Embed,
+        component::{ActionRow, SelectMenu, SelectMenuOption, SelectMenuType},
+    },
+    id::{Id, marker::UserMarker},
+};
+use twilight_util::builder::embed::EmbedBuilder;
+
+use crate::{
+    Context,
+    command::{BotCommand, acknowledge, deferred},
+    error::CommandError,
+    music::{
+        embeds,
+        player::{enqueue, join},
+        sources::ytdlp::{self, Entry},
+    },
+    utils::truncate,
+};
+
+const DEFAULT_RESULTS: i64 = 5;
+/// How long the requester has to pick a result.
+const SELECTION_TIMEOUT: Duration = Duration::from_secs(60);
+
+#[derive(Debug, CommandModel, CreateCommand)]
+#[command(
+    name = "search",
+    desc = "Search for a track and pick which one to queue."
+)]
+pub struct SearchCommand {
+    #[command(desc = "search term")]
+    pub query: String,
+    #[command(desc = "number of results to show", min_value = 1, max_value = 10)]
+    pub results: Option<i64>,
+}
+
+#[async_trait]
+impl BotCommand for SearchCommand {
+    async fn handle(
+        interaction: Interaction,
+        data: CommandData,
+        ctx: &Context,
+    ) -> anyhow::Result<()> {
+        let command = SearchCommand::from_interaction(data.into())?;
+
+        deferred(ctx, &interaction, command.search(&interaction, ctx)).await
+    }
+
+    /// Selections by the requester are awaited in [`SearchCommand::search`]; anyone else
+    /// is told the menu is not theirs.
+    async fn component(
+        interaction: Interaction,
+        data: MessageComponentInteractionData,
+        _ctx: &Context,
+    ) -> anyhow::Result<()> {
+        let requester = data
+            .custom_id
+            .rsplit(':')
+            .next()
+            .and_then(|id| id.parse::<Id<UserMarker>>().ok());
+
+        match requester {
+            Some(requester) if Some(requester) != interaction.author_id() => {
+                bail!(CommandError::PermissionDenied(format!(
+                    "Only {} can pick from these results.",
+                    requester.mention()
+                )))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+impl SearchCommand {
+    async fn search(self, interaction: &Interaction, ctx: &Context) -> anyhow::Result<Embed> {
+        let guild_id = interaction.guild_id.ok_or(CommandError::NotInGuild)?;
+        let requester = interaction.author().unwrap().id;
+
+        tracing::debug!(
+            "search command for {} by {}",
+            self.query,
+            requester.mention()
+        );
+
+        let count = self.results.unwrap_or(DEFAULT_RESULTS).clamp(1, 10) as usize;
+        let entries = match ytdlp::search(&self.query, count).await {
+            Ok(entries) if !entries.is_empty() => entries,
+            Ok(_) => bail!(CommandError::SourceFailed(self.query)),
+            Err(error) => {
+                tracing::warn!(?error, "failed to search for {}", self.query);
+                bail!(CommandError::SourceFailed(self.query));
+            }
+        };
+
+        let message = ctx
+            .client
+            .interaction(interaction.application_id)
+            .update_response(&interaction.token)
+            .embeds(Some(&[results(&self.query, &entries)]))
+            .components(Some(&[menu(&entries, requester)]))
+            .await?
+            .model()
+            .await?;
+
+        let selection = ctx
+            .standby
+            .wait_for_component(message.id, move |event: &Interaction| {
+                event.author_id() == Some(requester)
+            });
+        let selection = match tokio::time::timeout(SELECTION_TIMEOUT, selection).await {
+            Ok(Ok(selection)) => selection,
+            _ => {
+                return Ok(EmbedBuilder::new()
+                    .color(0xf04628)
+                    .title("Search timed out")
+                    .description(format!("No result was picked for `{}`.", self.query))
+                    .build());
+            }
+        };
+        acknowledge(ctx, &selection).await?;
+
+        let entry = match &selection.data {
+            Some(InteractionData::MessageComponent(data)) => data
+                .values
+                .first()
+                .and_then(|index| index.parse::<usize>().ok())
+                .and_then(|index| entries.get(index)),
+            _ => None,
+        };
+        let Some(entry) = entry else {
+            bail!("invalid search selection");
+        };
+
+        join(ctx, interaction).await?;
+        let track = entry.track(ctx.http.clone());
+        ctx.suggestions
+            .record(guild_id, embeds::title(&track.metadata), &track.query);
+        let channel_id = interaction.channel.as_ref().unwrap().id;
+        let metadata = enqueue(ctx, guild_id, requester, channel_id, track).await?;
+
+        Ok(embeds::queued(&metadata, requester).build())
+    }
+}
+
+/// Lists the results, numbered like the options of the menu.
+fn results(query: &str, entries: &[Entry]) -> Embed {
+    let description = entries
+        .iter()
+        .enumerate()
+        .map(|(index, entry)| {
+            let metadata = entry.metadata();
+            format!(
+                "`{}.` {} `{}`",
+                index + 1,
+                embeds::song(&metadata),
+                embeds::duration(&metadata)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    EmbedBuilder::new()
+        .color(0xf04628)
+        .title(format!("Results for {}", query))
+        .description(description)
+        .build()
+}
+
+fn menu(entries: &[Entry], requester: Id<UserMarker>) -> Component {
+    let options = entries
+        .iter()
+        .enumerate()
+        .map(|(index, entry)| {
+            let metadata = entry.metadata();
+            let channel = metadata.channel.as_deref().unwrap_or("Unknown channel");
+            let duration = embeds::duration(&metadata);
+
+            // Discord allows at most 100 characters in option labels and descriptions.
+            SelectMenuOption {
+                default: false,
+                description: Some(truncate(&format!("{} • {}", channel, duration), 100)),
+                emoji: None,
+                label: truncate(&format!("{}. {}", index + 1, embeds::title(&metadata)), 100),
+                value: index.to_string(),
+            }
+        })
+        .collect();
+
+    Component::ActionRow(ActionRow {
+        components: vec![Component::SelectMenu(SelectMenu {
+            channel_types: None,
+            custom_id: format!("search:select:{}", requester),
+            default_values: None,
+            disabled: false,
+            kind: SelectMenuType::Text,
+            max_values: Some(1),
+            min_values: Some(1),
+            options: Some(options),
+            placeholder: Some("Pick a track to queue".to_owned()),
+        })],
+    })
+}
@@ -0,0 +1,56 @@
+use anyhow::bail;
+use async_trait::async_trait;
+use twilight_interactions::command::{CommandModel, CreateCommand};
+use twilight_mention::Mention;
+use twilight_model::application::interaction::{Interaction, application_command::CommandData};
+use twilight_util::builder::InteractionResponseDataBuilder;
+
+use crate::{
+    Context,
+    command::{BotCommand, respond},
+    error::CommandError,
+    music::controls,
+    utils::{Seek, to_timestamp},
+};
+
+#[derive(Debug, CommandModel, CreateCommand)]
+#[command(name = "seek", desc = "Jump to a position in the current track.")]
+pub struct SeekCommand {
+    #[command(desc = "position like 90, 1:30 or 1:02:03, or an offset like +30 or -15")]
+    pub position: String,
+}
+
+#[async_trait]
+impl BotCommand for SeekCommand {
+    async fn handle(
+        interaction: Interaction,
+        data: CommandData,
+        ctx: &Context,
+    ) -> anyhow::Result<()> {
+        let guild_id = interaction.guild_id.ok_or(CommandError::NotInGuild)?;
+        let command = SeekCommand::from_interaction(data.into())?;
+
+        tracing::debug!(
+            "seek command in channel {} by {}",
+            interaction.channel.clone().unwrap().id,
+            interaction.author().unwrap().mention()
+        );
+
+        let Some(seek) = Seek::parse(&command.position) else {
+            bail!(CommandError::InvalidArgument(format!(
+                "`{}` is not a position. Use e.g. `90`, `1:30`, `+30` or `-15`.",
+                command.position
+            )));
+        };
+        let position = controls::seek(ctx, guild_id, seek).await?;
+
+        respond(
+            ctx,
+            &interaction,
+            InteractionResponseDataBuilder::new()
+                .content(format!("Seeked to {}", to_timestamp(position.as_secs())))
+                .build(),
+        )
+        .await
+    }
+}
@@ -0,0 +1,227 @@
+use anyhow::bail;
+use async_trait::async_trait;
+use twilight_interactions::command::{CommandModel, CreateCommand};
+use twilight_mention::Mention;
+use twilight_model::{
+    application::interaction::{Interaction, application_command::CommandData},
+    guild::Permissions,
+    id::{Id, marker::RoleMarker},
+};
+use twilight_util::builder::InteractionResponseDataBuilder;
+
+use crate::{
+    Context,
+    command::{BotCommand, respond},
+    error::CommandError,
+};
+
+#[derive(Debug, CommandModel, CreateCommand)]
+#[command(
+    name = "settings",
+    desc = "Change how the bot behaves in this server.",
+    default_permissions = "manage_guild"
+)]
+pub enum SettingsCommand {
+    #[command(name = "voteskip")]
+    VoteSkip(SettingsVoteSkip),
+    #[command(name = "dj")]
+    Dj(SettingsDj),
+    #[command(name = "allow")]
+    Allow(SettingsAllow),
+    #[command(name = "reset")]
+    Reset(SettingsReset),
+    #[command(name = "policy")]
+    Policy(SettingsPolicy),
+    #[command(name = "idle")]
+    Idle(SettingsIdle),
+}
+
+#[derive(Debug, CommandModel, CreateCommand)]
+#[command(
+    name = "voteskip",
+    desc = "Require a share of listeners to vote before a track is skipped."
+)]
+pub struct SettingsVoteSkip {
+    #[command(
+        desc = "share of listeners in percent, or 0 to skip right away",
+        min_value = 0,
+        max_value = 100
+    )]
+    pub percent: i64,
+}
+
+#[derive(Debug, CommandModel, CreateCommand)]
+#[command(
+    name = "dj",
+    desc = "Set the role allowed to control playback without voting."
+)]
+pub struct SettingsDj {
+    #[command(desc = "the DJ role, or nothing to remove it")]
+    pub role: Option<Id<RoleMarker>>,
+}
+
+#[derive(Debug, CommandModel, CreateCommand)]
+#[command(
+    name = "allow",
+    desc = "Limit a command to the given roles, adding this one."
+)]
+pub struct SettingsAllow {
+    #[command(desc = "name of the command, without the slash")]
+    pub command: String,
+    #[command(desc = "role allowed to use it")]
+    pub role: Id<RoleMarker>,
+}
+
+#[derive(Debug, CommandModel, CreateCommand)]
+#[command(name = "reset", desc = "Open a command to every member again.")]
+pub struct SettingsReset {
+    #[command(desc = "name of the command, without the slash")]
+    pub command: String,
+}
+
+#[derive(Debug, CommandModel, CreateCommand)]
+#[command(
+    name = "policy",
+    desc = "Change who counts as allowed to control playback."
+)]
+pub struct SettingsPolicy {
+    #[command(desc = "whether requesters can always skip their own tracks")]
+    pub requester_skip: Option<bool>,
+    #[command(desc = "whether a member alone with the bot counts as a DJ")]
+    pub alone_is_dj: Option<bool>,
+}
+
+#[derive(Debug, CommandModel, CreateCommand)]
+#[command(
+    name = "idle",
+    desc = "Change what happens once everyone leaves the voice channel."
+)]
+pub struct SettingsIdle {
+    #[command(desc = "pause until someone joins again, instead of leaving")]
+    pub pause_when_alone: bool,
+}
+
+fn manage_guild() -> Permissions {
+    Permissions::MANAGE_GUILD
+}
+
+#[async_trait]
+impl BotCommand for SettingsCommand {
+    async fn handle(
+        interaction: Interaction,
+        data: CommandData,
+        ctx: &Context,
+    ) -> anyhow::Result<()> {
+        let guild_id = interaction.guild_id.ok_or(CommandError::NotInGuild)?;
+        let command = SettingsCommand::from_interaction(data.into())?;
+
+        tracing::debug!(
+            "settings command in channel {} by {}",
+            interaction.channel.clone().unwrap().id,
+            interaction.author().unwrap().mention()
+        );
+
+        let content = match command {
+            SettingsCommand::VoteSkip(SettingsVoteSkip { percent }) => {
+                let percent = percent.clamp(0, 100) as u8;
+                ctx.settings
+                    .update(guild_id, |settings| settings.vote_skip = percent)
+                    .await?;
+                match percent {
+                    0 => "Tracks are skipped right away".to_owned(),
+                    percent => format!("Skipping needs votes from {}% of listeners", percent),
+                }
+            }
+            SettingsCommand::Dj(SettingsDj { role }) => {
+                ctx.settings
+                    .update(guild_id, |settings| settings.dj_role = role)
+                    .await?;
+                match role {
+                    Some(role) => format!("{} is now the DJ role", role.mention()),
+                    None => "Removed the DJ role".to_owned(),
+                }
+            }
+            SettingsCommand::Allow(SettingsAllow { command, role }) => {
+                let command = command_name(ctx, &command)?;
+                ctx.settings
+                    .update(guild_id, |settings| {
+                        let roles = settings.allowed_roles.entry(command.clone()).or_default();
+                        if !roles.contains(&role) {
+                            roles.push(role);
+                        }
+                    })
+                    .await?;
+                format!("{} can now use /{}", role.mention(), command)
+            }
+            SettingsCommand::Reset(SettingsReset { command }) => {
+                let command = command_name(ctx, &command)?;
+                ctx.settings
+                    .update(guild_id, |settings| settings.allowed_roles.remove(&command))
+                    .await?;
+                format!("Every member can use /{} again", command)
+            }
+            SettingsCommand::Policy(SettingsPolicy {
+                requester_skip,
+                alone_is_dj,
+            }) => {
+                let settings = ctx
+                    .settings
+                    .update(guild_id, |settings| {
+                        if let Some(requester_skip) = requester_skip {
+                            settings.requester_skip = requester_skip;
+                        }
+                        if let Some(alone_is_dj) = alone_is_dj {
+                            settings.alone_is_dj = alone_is_dj;
+                        }
+                        settings.clone()
+                    })
+                    .await?;
+                let requester_skip = match settings.requester_skip {
+                    true => "can",
+                    false => "cannot",
+                };
+                let alone_is_dj = match settings.alone_is_dj {
+                    true => "do",
+                    false => "do not",
+                };
+                format!(
+                    "Requesters {} skip their own tracks; lone listeners {} count as DJs",
+                    requester_skip, alone_is_dj
+                )
+            }
+            SettingsCommand::Idle(SettingsIdle { pause_when_alone }) => {
+                ctx.settings
+                    .update(guild_id, |settings| {
+                        settings.pause_when_alone = pause_when_alone
+                    })
+                    .await?;
+                match pause_when_alone {
+                    true => "The music pauses once everyone leaves".to_owned(),
+                    false => "The bot leaves once everyone else does".to_owned(),
+                }
+            }
+        };
+
+        respond(
+            ctx,
+            &interaction,
+            InteractionResponseDataBuilder::new()
+                .content(content)
+                .build(),
+        )
+        .await
+    }
+}
+
+/// Normalizes a command name typed by the user, failing if there is no such command.
+fn command_name(ctx: &Context, command: &str) -> anyhow::Result<String> {
+    let command = command.trim().trim_start_matches('/').to_lowercase();
+    if !ctx.commands.contains(&command) {
+        bail!(CommandError::InvalidArgument(format!(
+            "There is no /{} command.",
+            command
+        )));
+    }
+
+    Ok(command)
+}
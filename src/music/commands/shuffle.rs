@@ -0,0 +1,44 @@
+use async_trait::async_trait;
+use twilight_interactions::command::{CommandModel, CreateCommand};
+use twilight_mention::Mention;
+use twilight_model::application::interaction::{Interaction, application_command::CommandData};
+use twilight_util::builder::InteractionResponseDataBuilder;
+
+use crate::{
+    Context,
+    command::{BotCommand, respond},
+    error::CommandError,
+    music::controls,
+};
+
+#[derive(Debug, CommandModel, CreateCommand)]
+#[command(name = "shuffle", desc = "Shuffle the upcoming tracks.")]
+pub struct ShuffleCommand;
+
+#[async_trait]
+impl BotCommand for ShuffleCommand {
+    async fn handle(
+        interaction: Interaction,
+        _data: CommandData,
+        ctx: &Context,
+    ) -> anyhow::Result<()> {
+        let guild_id = interaction.guild_id.ok_or(CommandError::NotInGuild)?;
+
+        tracing::debug!(
+            "shuffle command in channel {} by {}",
+            interaction.channel.clone().unwrap().id,
+            interaction.author().unwrap().mention()
+        );
+
+        controls::shuffle(ctx, guild_id).await?;
+
+        respond(
+            ctx,
+            &interaction,
+            InteractionResponseDataBuilder::new()
+                .content("Shuffling")
+                .build(),
+        )
+        .await
+    }
+}
@@ -1,25 +1,34 @@
+use anyhow::bail;
+use async_trait::async_trait;
 use twilight_interactions::command::{CommandModel, CreateCommand};
 use twilight_mention::Mention;
-use twilight_model::{
-    application::interaction::{Interaction, application_command::CommandData},
-    http::interaction::{InteractionResponse, InteractionResponseType},
-};
+use twilight_model::application::interaction::{Interaction, application_command::CommandData};
 use twilight_util::builder::InteractionResponseDataBuilder;
 
-use crate::Context;
+use crate::{
+    Context,
+    command::{BotCommand, respond},
+    error::CommandError,
+    music::controls::{self, SkipVote},
+    permissions,
+};
 
 #[derive(Debug, CommandModel, CreateCommand)]
 #[command(name = "skip", desc = "Skip the current track.")]
-pub struct SkipCommand;
+pub struct SkipCommand {
+    #[command(desc = "position to jump to, as shown by /queue", min_value = 1)]
+    pub to: Option<i64>,
+}
 
-impl SkipCommand {
-    pub async fn handle(
+#[async_trait]
+impl BotCommand for SkipCommand {
+    async fn handle(
         interaction: Interaction,
-        _data: CommandData,
+        data: CommandData,
         ctx: &Context,
     ) -> anyhow::Result<()> {
-        let client = ctx.client.interaction(interaction.application_id);
-        let guild_id = interaction.guild_id.unwrap();
+        let guild_id = interaction.guild_id.ok_or(CommandError::NotInGuild)?;
+        let command = SkipCommand::from_interaction(data.into())?;
 
         tracing::debug!(
             "skip command in channel {} by {}",
@@ -27,24 +36,40 @@ impl SkipCommand {
             interaction.author().unwrap().mention()
         );
 
-        if let Some(call_lock) = ctx.songbird.get(guild_id) {
-            let call = call_lock.lock().await;
-            call.queue().skip()?;
-        }
-
-        let response = InteractionResponse {
-            kind: InteractionResponseType::ChannelMessageWithSource,
-            data: Some(
-                InteractionResponseDataBuilder::new()
-                    .content("Skipping")
-                    .build(),
-            ),
-        };
+        let user_id = interaction.author().unwrap().id;
+        let roles = interaction
+            .member
+            .as_ref()
+            .map(|member| member.roles.as_slice())
+            .unwrap_or_default();
+
+        let content = match command.to {
+            // Jumping ahead drops tracks other listeners queued, so it is not put to a vote.
+            Some(position) => {
+                if ctx.settings.get(guild_id).vote_skip > 0
+                    && !permissions::is_dj(ctx, guild_id, user_id, roles).await
+                {
+                    bail!(CommandError::PermissionDenied(
+                        "Only DJs can jump ahead while vote-skip is on.".to_owned()
+                    ));
+                }
 
-        client
-            .create_response(interaction.id, &interaction.token, &response)
-            .await?;
+                controls::skip_to(ctx, guild_id, position.max(1) as usize).await?;
+                format!("Skipping to track {}", position)
+            }
+            None => match controls::vote_skip(ctx, guild_id, user_id, roles).await? {
+                SkipVote::Skipped => "Skipping".to_owned(),
+                SkipVote::Counted { votes, needed } => controls::vote_message(votes, needed),
+            },
+        };
 
-        Ok(())
+        respond(
+            ctx,
+            &interaction,
+            InteractionResponseDataBuilder::new()
+                .content(content)
+                .build(),
+        )
+        .await
     }
 }
@@ -0,0 +1,57 @@
+use async_trait::async_trait;
+use twilight_interactions::command::{CommandModel, CreateCommand};
+use twilight_mention::Mention;
+use twilight_model::application::interaction::{Interaction, application_command::CommandData};
+use twilight_util::builder::InteractionResponseDataBuilder;
+
+use crate::{
+    Context,
+    command::{BotCommand, respond},
+    error::CommandError,
+    music::controls,
+};
+
+#[derive(Debug, CommandModel, CreateCommand)]
+#[command(name = "volume", desc = "Change the playback volume.")]
+pub struct VolumeCommand {
+    #[command(desc = "volume in percent", min_value = 0, max_value = 200)]
+    pub percent: i64,
+    #[command(desc = "only change the current track")]
+    pub track: Option<bool>,
+}
+
+#[async_trait]
+impl BotCommand for VolumeCommand {
+    async fn handle(
+        interaction: Interaction,
+        data: CommandData,
+        ctx: &Context,
+    ) -> anyhow::Result<()> {
+        let guild_id = interaction.guild_id.ok_or(CommandError::NotInGuild)?;
+        let command = VolumeCommand::from_interaction(data.into())?;
+
+        tracing::debug!(
+            "volume command in channel {} by {}",
+            interaction.channel.clone().unwrap().id,
+            interaction.author().unwrap().mention()
+        );
+
+        let percent = command.percent.clamp(0, 200) as u16;
+        let track_only = command.track.unwrap_or(false);
+        controls::set_volume(ctx, guild_id, percent, track_only).await?;
+
+        let content = match track_only {
+            true => format!("Playing the current track at {}%", percent),
+            false => format!("Volume set to {}%", percent),
+        };
+
+        respond(
+            ctx,
+            &interaction,
+            InteractionResponseDataBuilder::new()
+                .content(content)
+                .build(),
+        )
+        .await
+    }
+}
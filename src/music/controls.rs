@@ -0,0 +1,581 @@
+use std::{collections::HashSet, time::Duration};
+
+use rand::seq::SliceRandom;
+use songbird::{
+    Call,
+    tracks::{PlayMode, Queued, TrackHandle, TrackQueue},
+};
+use twilight_model::{
+    application::interaction::{Interaction, message_component::MessageComponentInteractionData},
+    channel::message::{
+        Component, MessageFlags,
+        component::{ActionRow, Button, ButtonStyle},
+    },
+    id::{
+        Id,
+        marker::{GuildMarker, RoleMarker, UserMarker},
+    },
+};
+use twilight_util::builder::InteractionResponseDataBuilder;
+
+use crate::{
+    Context,
+    command::{respond, update},
+    error::CommandError,
+    music::{
+        player,
+        state::{LoopMode, SkipVotes},
+        voice,
+    },
+    permissions,
+    utils::Seek,
+};
+
+pub async fn current(ctx: &Context, guild_id: Id<GuildMarker>) -> Option<TrackHandle> {
+    let call_lock = ctx.songbird.get(guild_id)?;
+    let call = call_lock.lock().await;
+    call.queue().current()
+}
+
+pub async fn pause(ctx: &Context, guild_id: Id<GuildMarker>) -> anyhow::Result<()> {
+    let call_lock = ctx
+        .songbird
+        .get(guild_id)
+        .ok_or(CommandError::NothingPlaying)?;
+    let call = call_lock.lock().await;
+    playing(&call)?.pause()?;
+
+    Ok(())
+}
+
+pub async fn resume(ctx: &Context, guild_id: Id<GuildMarker>) -> anyhow::Result<()> {
+    let call_lock = ctx
+        .songbird
+        .get(guild_id)
+        .ok_or(CommandError::NothingPlaying)?;
+    let call = call_lock.lock().await;
+    playing(&call)?.resume()?;
+
+    Ok(())
+}
+
+pub async fn skip(ctx: &Context, guild_id: Id<GuildMarker>) -> anyhow::Result<()> {
+    let call_lock = ctx
+        .songbird
+        .get(guild_id)
+        .ok_or(CommandError::NothingPlaying)?;
+    let call = call_lock.lock().await;
+    playing(&call)?.skip()?;
+
+    Ok(())
+}
+
+/// Skips the current track and every upcoming one before `position`.
+pub async fn skip_to(
+    ctx: &Context,
+    guild_id: Id<GuildMarker>,
+    position: usize,
+) -> anyhow::Result<()> {
+    let call_lock = ctx
+        .songbird
+        .get(guild_id)
+        .ok_or(CommandError::NothingPlaying)?;
+    let call = call_lock.lock().await;
+    let queue = playing(&call)?;
+
+    let upcoming = queue.len() - 1;
+    if position == 0 || position > upcoming {
+        return Err(invalid_range(position, position, upcoming).into());
+    }
+
+    discard(queue.modify_queue(|queue| queue.drain(1..position).collect()));
+    queue.skip()?;
+
+    Ok(())
+}
+
+/// What came of a request to skip the current track.
+pub enum SkipVote {
+    Skipped,
+    /// The vote was counted, but more are needed.
+    Counted {
+        votes: usize,
+        needed: usize,
+    },
+}
+
+/// Skips the current track for `user`, or counts their vote if the guild has vote-skip on.
+///
+/// The requester of the track, if the guild allows it, and DJs skip without a vote. Only
+/// votes of users still in the voice channel count, and the share needed is taken from its
+/// current listeners.
+pub async fn vote_skip(
+    ctx: &Context,
+    guild_id: Id<GuildMarker>,
+    user_id: Id<UserMarker>,
+    roles: &[Id<RoleMarker>],
+) -> anyhow::Result<SkipVote> {
+    let handle = current(ctx, guild_id)
+        .await
+        .ok_or(CommandError::NothingPlaying)?;
+    let settings = ctx.settings.get(guild_id);
+    let requester = ctx
+        .trackdata
+        .get(guild_id, handle.uuid())
+        .map(|data| data.requester);
+
+    if settings.vote_skip == 0
+        || (settings.requester_skip && requester == Some(user_id))
+        || permissions::is_dj(ctx, guild_id, user_id, roles).await
+    {
+        skip(ctx, guild_id).await?;
+        return Ok(SkipVote::Skipped);
+    }
+
+    let listeners = voice::listeners(ctx, guild_id).await;
+    if !listeners.contains(&user_id) {
+        return Err(CommandError::PermissionDenied(
+            "Only listeners in the voice channel can vote to skip.".to_owned(),
+        )
+        .into());
+    }
+    let needed = (listeners.len() * usize::from(settings.vote_skip))
+        .div_ceil(100)
+        .max(1);
+
+    let votes = ctx.guilds.update(guild_id, |state| {
+        if state
+            .skip_votes
+            .as_ref()
+            .is_none_or(|votes| votes.track != handle.uuid())
+        {
+            state.skip_votes = Some(SkipVotes {
+                track: handle.uuid(),
+                voters: HashSet::new(),
+            });
+        }
+
+        let votes = state.skip_votes.as_mut().expect("votes were just set");
+        votes.voters.insert(user_id);
+        votes
+            .voters
+            .iter()
+            .filter(|voter| listeners.contains(voter))
+            .count()
+    });
+
+    if votes < needed {
+        return Ok(SkipVote::Counted { votes, needed });
+    }
+
+    ctx.guilds.update(guild_id, |state| state.skip_votes = None);
+    skip(ctx, guild_id).await?;
+
+    Ok(SkipVote::Skipped)
+}
+
+pub async fn stop(ctx: &Context, guild_id: Id<GuildMarker>) -> anyhow::Result<()> {
+    let call_lock = ctx
+        .songbird
+        .get(guild_id)
+        .ok_or(CommandError::NothingPlaying)?;
+    let call = call_lock.lock().await;
+    playing(&call)?.stop();
+
+    Ok(())
+}
+
+/// Stops playback and disconnects from the voice channel of the guild.
+pub async fn leave(ctx: &Context, guild_id: Id<GuildMarker>) -> anyhow::Result<()> {
+    let call_lock = ctx
+        .songbird
+        .get(guild_id)
+        .ok_or(CommandError::NothingPlaying)?;
+    call_lock.lock().await.queue().stop();
+    ctx.songbird.remove(guild_id).await?;
+    // The driver is gone before the stopped tracks could report their end.
+    ctx.trackdata.clear(guild_id);
+
+    let timer = ctx.guilds.update(guild_id, |state| {
+        state.skip_votes = None;
+        state.alone = false;
+        state.paused_alone = false;
+        state.recovering = false;
+        state.idle_timer.take()
+    });
+    if let Some(timer) = timer {
+        timer.abort();
+    }
+
+    Ok(())
+}
+
+/// Returns the queue of `call`, failing if there is nothing in it.
+fn playing(call: &Call) -> Result<&TrackQueue, CommandError> {
+    match call.queue() {
+        queue if queue.is_empty() => Err(CommandError::NothingPlaying),
+        queue => Ok(queue),
+    }
+}
+
+/// Sets the loop mode of the guild, looping the current track only in [`LoopMode::Track`].
+///
+/// Tracks starting later are looped by the playable handler.
+pub async fn set_loop(
+    ctx: &Context,
+    guild_id: Id<GuildMarker>,
+    mode: LoopMode,
+) -> anyhow::Result<()> {
+    ctx.guilds.update(guild_id, |state| state.loop_mode = mode);
+
+    if let Some(handle) = current(ctx, guild_id).await {
+        match mode {
+            LoopMode::Track => handle.enable_loop()?,
+            LoopMode::Off | LoopMode::Queue => handle.disable_loop()?,
+        }
+    }
+
+    Ok(())
+}
+
+/// Shuffles the upcoming tracks, leaving the current one in place.
+pub async fn shuffle(ctx: &Context, guild_id: Id<GuildMarker>) -> anyhow::Result<()> {
+    if let Some(call_lock) = ctx.songbird.get(guild_id) {
+        let call = call_lock.lock().await;
+        call.queue().modify_queue(|queue| {
+            if queue.len() > 2 {
+                queue.make_contiguous()[1..].shuffle(&mut rand::rng());
+            }
+        });
+    }
+
+    Ok(())
+}
+
+/// Seeks within the current track, returning the position it landed on.
+///
+/// Targets past the end are clamped to it. Tracks without a known duration are treated as
+/// live streams, which cannot seek.
+pub async fn seek(
+    ctx: &Context,
+    guild_id: Id<GuildMarker>,
+    seek: Seek,
+) -> anyhow::Result<Duration> {
+    let handle = current(ctx, guild_id)
+        .await
+        .ok_or(CommandError::NothingPlaying)?;
+    let duration = ctx
+        .trackdata
+        .get(guild_id, handle.uuid())
+        .and_then(|data| data.metadata.duration)
+        .ok_or_else(|| CommandError::InvalidArgument("Live streams cannot seek.".to_owned()))?;
+
+    let position = handle.get_info().await?.position;
+    let target = Duration::from_secs(seek.target(position.as_secs())).min(duration);
+
+    match handle.seek_async(target).await {
+        Ok(position) => Ok(position),
+        Err(error) => {
+            tracing::debug!(?error, "failed to seek");
+            Err(
+                CommandError::InvalidArgument("This track does not support seeking.".to_owned())
+                    .into(),
+            )
+        }
+    }
+}
+
+/// Sets the volume of the guild in percent, applying it to every queued track that has
+/// no volume of its own. With `track_only`, only the current track is changed.
+pub async fn set_volume(
+    ctx: &Context,
+    guild_id: Id<GuildMarker>,
+    percent: u16,
+    track_only: bool,
+) -> anyhow::Result<()> {
+    if track_only {
+        let handle = current(ctx, guild_id)
+            .await
+            .ok_or(CommandError::NothingPlaying)?;
+        let data = ctx
+            .trackdata
+            .get(guild_id, handle.uuid())
+            .ok_or(CommandError::NothingPlaying)?;
+
+        handle.set_volume(player::volume(percent))?;
+        *data.volume.write().unwrap() = Some(percent);
+
+        return Ok(());
+    }
+
+    ctx.settings
+        .update(guild_id, |settings| settings.volume = percent)
+        .await?;
+
+    let Some(call_lock) = ctx.songbird.get(guild_id) else {
+        return Ok(());
+    };
+    let tracks = call_lock.lock().await.queue().current_queue();
+    for handle in tracks {
+        let overridden = ctx
+            .trackdata
+            .get(guild_id, handle.uuid())
+            .is_some_and(|data| data.volume.read().unwrap().is_some());
+
+        if !overridden && let Err(error) = handle.set_volume(player::volume(percent)) {
+            tracing::debug!(?error, "failed to set volume of queued track");
+        }
+    }
+
+    Ok(())
+}
+
+/// Removes the upcoming tracks at positions `from..=to`, returning how many were removed.
+///
+/// Positions count upcoming tracks from 1, as listed by `/queue`.
+pub async fn remove(
+    ctx: &Context,
+    guild_id: Id<GuildMarker>,
+    from: usize,
+    to: usize,
+) -> anyhow::Result<usize> {
+    let call_lock = ctx
+        .songbird
+        .get(guild_id)
+        .ok_or(CommandError::NothingPlaying)?;
+    let call = call_lock.lock().await;
+    let queue = playing(&call)?;
+
+    let upcoming = queue.len() - 1;
+    if from == 0 || from > to || to > upcoming {
+        return Err(invalid_range(from, to, upcoming).into());
+    }
+
+    let removed = queue.modify_queue(|queue| queue.drain(from..=to).collect());
+    Ok(discard(removed))
+}
+
+/// Removes the upcoming tracks requested by `requester`, returning how many were removed.
+pub async fn remove_by(
+    ctx: &Context,
+    guild_id: Id<GuildMarker>,
+    requester: Id<UserMarker>,
+) -> anyhow::Result<usize> {
+    let call_lock = ctx
+        .songbird
+        .get(guild_id)
+        .ok_or(CommandError::NothingPlaying)?;
+    let call = call_lock.lock().await;
+
+    let removed = playing(&call)?.modify_queue(|queue| {
+        let mut removed = Vec::new();
+        let mut index = 1;
+        while index < queue.len() {
+            let data = ctx.trackdata.get(guild_id, queue[index].uuid());
+            match data {
+                Some(data) if data.requester == requester => removed.extend(queue.remove(index)),
+                _ => index += 1,
+            }
+        }
+        removed
+    });
+
+    Ok(discard(removed))
+}
+
+/// Moves the upcoming track at position `from` to position `to`, returning its handle.
+pub async fn move_track(
+    ctx: &Context,
+    guild_id: Id<GuildMarker>,
+    from: usize,
+    to: usize,
+) -> anyhow::Result<TrackHandle> {
+    let call_lock = ctx
+        .songbird
+        .get(guild_id)
+        .ok_or(CommandError::NothingPlaying)?;
+    let call = call_lock.lock().await;
+    let queue = playing(&call)?;
+
+    let upcoming = queue.len() - 1;
+    for position in [from, to] {
+        if position == 0 || position > upcoming {
+            return Err(invalid_range(position, position, upcoming).into());
+        }
+    }
+
+    let track = queue.modify_queue(|queue| {
+        let track = queue.remove(from)?;
+        let handle = (*track).clone();
+        queue.insert(to, track);
+        Some(handle)
+    });
+
+    Ok(track.expect("position was checked"))
+}
+
+/// Removes every upcoming track, keeping the current one playing.
+pub async fn clear(ctx: &Context, guild_id: Id<GuildMarker>) -> anyhow::Result<usize> {
+    let call_lock = ctx
+        .songbird
+        .get(guild_id)
+        .ok_or(CommandError::NothingPlaying)?;
+    let call = call_lock.lock().await;
+
+    let removed = playing(&call)?.modify_queue(|queue| queue.drain(1..).collect());
+    Ok(discard(removed))
+}
+
+/// Stops tracks taken out of the queue, so their end handlers release their data.
+fn discard(tracks: Vec<Queued>) -> usize {
+    for track in &tracks {
+        if let Err(error) = track.stop() {
+            tracing::debug!(?error, "failed to stop removed track");
+        }
+    }
+
+    tracks.len()
+}
+
+fn invalid_range(from: usize, to: usize, upcoming: usize) -> CommandError {
+    let positions = match from == to {
+        true => format!("position {}", from),
+        false => format!("positions {} to {}", from, to),
+    };
+
+    CommandError::InvalidArgument(match upcoming {
+        0 => "There are no upcoming tracks.".to_owned(),
+        upcoming => format!(
+            "There is no track at {}; the queue has {} upcoming.",
+            positions, upcoming
+        ),
+    })
+}
+
+/// Builds the control buttons for the given play state.
+pub fn components(paused: bool, loop_mode: LoopMode) -> Vec<Component> {
+    let play_button = match paused {
+        true => button("Resume", "nowplaying:resume", ButtonStyle::Success),
+        false => button("Pause", "nowplaying:pause", ButtonStyle::Secondary),
+    };
+    let loop_style = match loop_mode {
+        LoopMode::Off => ButtonStyle::Secondary,
+        LoopMode::Track | LoopMode::Queue => ButtonStyle::Success,
+    };
+    let loop_label = format!("Loop: {}", loop_mode.label());
+
+    vec![Component::ActionRow(ActionRow {
+        components: vec![
+            play_button,
+            button("Skip", "nowplaying:skip", ButtonStyle::Secondary),
+            button("Stop", "nowplaying:stop", ButtonStyle::Danger),
+            button(&loop_label, "nowplaying:loop", loop_style),
+            button("Shuffle", "nowplaying:shuffle", ButtonStyle::Secondary),
+        ],
+    })]
+}
+
+/// Builds the control buttons reflecting the state of `handle`.
+pub async fn components_for(
+    ctx: &Context,
+    guild_id: Id<GuildMarker>,
+    handle: &TrackHandle,
+) -> Vec<Component> {
+    match handle.get_info().await {
+        Ok(state) => components(
+            matches!(state.playing, PlayMode::Pause),
+            ctx.guilds.get(guild_id).loop_mode,
+        ),
+        Err(_) => Vec::new(),
+    }
+}
+
+pub async fn handle_component(
+    interaction: Interaction,
+    data: MessageComponentInteractionData,
+    ctx: &Context,
+) -> anyhow::Result<()> {
+    let guild_id = interaction.guild_id.ok_or(CommandError::NotInGuild)?;
+
+    let rows = match current(ctx, guild_id).await {
+        Some(handle) => {
+            let state = handle.get_info().await?;
+            let paused = matches!(state.playing, PlayMode::Pause);
+            let loop_mode = ctx.guilds.get(guild_id).loop_mode;
+
+            match data.custom_id.as_str() {
+                "nowplaying:pause" => {
+                    pause(ctx, guild_id).await?;
+                    components(true, loop_mode)
+                }
+                "nowplaying:resume" => {
+                    resume(ctx, guild_id).await?;
+                    components(false, loop_mode)
+                }
+                "nowplaying:skip" => {
+                    let user_id = interaction.author().unwrap().id;
+                    let roles = interaction
+                        .member
+                        .as_ref()
+                        .map(|member| member.roles.as_slice())
+                        .unwrap_or_default();
+
+                    match vote_skip(ctx, guild_id, user_id, roles).await? {
+                        SkipVote::Skipped => Vec::new(),
+                        SkipVote::Counted { votes, needed } => {
+                            // The message stays as it is; only the voter learns the tally.
+                            return respond(
+                                ctx,
+                                &interaction,
+                                InteractionResponseDataBuilder::new()
+                                    .content(vote_message(votes, needed))
+                                    .flags(MessageFlags::EPHEMERAL)
+                                    .build(),
+                            )
+                            .await;
+                        }
+                    }
+                }
+                "nowplaying:stop" => {
+                    stop(ctx, guild_id).await?;
+                    Vec::new()
+                }
+                "nowplaying:loop" => {
+                    set_loop(ctx, guild_id, loop_mode.next()).await?;
+                    components(paused, loop_mode.next())
+                }
+                "nowplaying:shuffle" => {
+                    shuffle(ctx, guild_id).await?;
+                    components(paused, loop_mode)
+                }
+                custom_id => anyhow::bail!("unknown control: {}", custom_id),
+            }
+        }
+        None => Vec::new(),
+    };
+
+    update(
+        ctx,
+        &interaction,
+        InteractionResponseDataBuilder::new()
+            .components(rows)
+            .build(),
+    )
+    .await
+}
+
+pub fn vote_message(votes: usize, needed: usize) -> String {
+    format!("Voted to skip ({}/{} votes)", votes, needed)
+}
+
+fn button(label: &str, custom_id: &str, style: ButtonStyle) -> Component {
+    Component::Button(Button {
+        custom_id: Some(custom_id.to_owned()),
+        disabled: false,
+        emoji: None,
+        label: Some(label.to_owned()),
+        style,
+        url: None,
+        sku_id: None,
+    })
+}
@@ -0,0 +1,169 @@
+use std::time::Duration;
+
+use songbird::input::AuxMetadata;
+use twilight_mention::Mention;
+use twilight_model::id::{Id, marker::UserMarker};
+use twilight_util::builder::embed::{EmbedBuilder, EmbedFieldBuilder, ImageSource};
+
+use crate::{
+    music::{state::LoopMode, track::TrackData},
+    utils::{progress_bar, to_timestamp},
+};
+
+/// Builds the "Now playing" embed, with a progress bar if the playback `position` is known.
+pub fn now_playing(
+    data: &TrackData,
+    position: Option<Duration>,
+    loop_mode: LoopMode,
+) -> EmbedBuilder {
+    let mut embed = EmbedBuilder::new()
+        .color(0xf04628)
+        .title("Now playing")
+        .field(EmbedFieldBuilder::new("Song", song(&data.metadata)).inline())
+        .field(EmbedFieldBuilder::new("Duration", duration(&data.metadata)).inline())
+        .field(
+            EmbedFieldBuilder::new("Requested by", format!("{}", data.requester.mention()))
+                .inline(),
+        );
+
+    if let Some(title) = data.stream_title.read().unwrap().as_deref() {
+        embed = embed.field(EmbedFieldBuilder::new("On air", title));
+    }
+
+    if let Some(artist) = &data.metadata.artist {
+        embed = embed.field(EmbedFieldBuilder::new("Artist", artist).inline());
+    }
+
+    if loop_mode != LoopMode::Off {
+        embed = embed.field(EmbedFieldBuilder::new("Loop", loop_mode.label()).inline());
+    }
+
+    if let Some(position) = position {
+        let progress = match data.metadata.duration {
+            Some(duration) => format!(
+                "`{}` {} `{}`",
+                to_timestamp(position.as_secs()),
+                progress_bar(position.as_secs(), duration.as_secs()),
+                to_timestamp(duration.as_secs())
+            ),
+            None => format!("`{}`", to_timestamp(position.as_secs())),
+        };
+        embed = embed.field(EmbedFieldBuilder::new("Progress", progress));
+    }
+
+    let image = match &data.artwork {
+        Some(artwork) => ImageSource::attachment(artwork.file_name()).ok(),
+        None => thumbnail(&data.metadata),
+    };
+
+    match image {
+        Some(image) => embed.image(image),
+        None => embed,
+    }
+}
+
+/// Builds the embed confirming that a track was added to the queue.
+pub fn queued(metadata: &AuxMetadata, requester: Id<UserMarker>) -> EmbedBuilder {
+    let mut embed = EmbedBuilder::new()
+        .color(0xf04628)
+        .title(title(metadata))
+        .description(format!("Requested by {}", requester.mention()));
+
+    if let Some(url) = &metadata.source_url {
+        embed = embed.url(url);
+    }
+
+    match thumbnail(metadata) {
+        Some(thumbnail) => embed.thumbnail(thumbnail),
+        None => embed,
+    }
+}
+
+pub fn title(metadata: &AuxMetadata) -> &str {
+    metadata.title.as_deref().unwrap_or("Unknown title")
+}
+
+/// The track title, linking to its source if known.
+pub fn song(metadata: &AuxMetadata) -> String {
+    match &metadata.source_url {
+        Some(url) => format!("[{}]({})", title(metadata), url),
+        None => title(metadata).to_owned(),
+    }
+}
+
+/// The track duration, or "Live" for streams without one.
+pub fn duration(metadata: &AuxMetadata) -> String {
+    match metadata.duration {
+        Some(duration) => to_timestamp(duration.as_secs()),
+        None => "Live".to_owned(),
+    }
+}
+
+fn thumbnail(metadata: &AuxMetadata) -> Option<ImageSource> {
+    ImageSource::url(metadata.thumbnail.as_ref()?).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::RwLock,
+        time::{Duration, SystemTime},
+    };
+
+    use super::*;
+
+    fn unknown_track() -> TrackData {
+        TrackData {
+            requester: Id::new(1),
+            channel_id: Id::new(2),
+            metadata: AuxMetadata::default(),
+            queued_at: SystemTime::now(),
+            query: String::new(),
+            artwork: None,
+            icy: false,
+            stream_title: RwLock::new(None),
+            volume: RwLock::new(None),
+        }
+    }
+
+    #[test]
+    fn describes_missing_metadata() {
+        let metadata = AuxMetadata::default();
+
+        assert_eq!(title(&metadata), "Unknown title");
+        assert_eq!(song(&metadata), "Unknown title");
+        assert_eq!(duration(&metadata), "Live");
+    }
+
+    #[test]
+    fn queued_without_metadata() {
+        let embed = queued(&AuxMetadata::default(), Id::new(1)).build();
+
+        assert_eq!(embed.title.as_deref(), Some("Unknown title"));
+        assert_eq!(embed.url, None);
+        assert!(embed.thumbnail.is_none());
+    }
+
+    #[test]
+    fn now_playing_without_metadata() {
+        let data = unknown_track();
+
+        for position in [None, Some(Duration::from_secs(42))] {
+            let embed = now_playing(&data, position, LoopMode::Off).build();
+            let field = |name: &str| {
+                embed
+                    .fields
+                    .iter()
+                    .find(|field| field.name == name)
+                    .map(|field| field.value.as_str())
+            };
+
+            assert_eq!(field("Song"), Some("Unknown title"));
+            assert_eq!(field("Duration"), Some("Live"));
+            assert_eq!(field("Artist"), None);
+            assert_eq!(embed.url, None);
+            assert!(embed.image.is_none());
+            assert!(embed.thumbnail.is_none());
+        }
+    }
+}
@@ -1,58 +1,238 @@
+use std::{sync::Arc, time::Duration};
+
 use async_trait::async_trait;
-use songbird::{Event, EventContext, EventHandler, input::AuxMetadata};
-use twilight_mention::Mention;
-use twilight_model::id::{
-    Id,
-    marker::{ChannelMarker, UserMarker},
+use songbird::{
+    Event, EventContext, EventHandler,
+    tracks::{PlayMode, TrackHandle},
+};
+use twilight_model::{
+    http::attachment::Attachment,
+    id::{
+        Id,
+        marker::{GuildMarker, MessageMarker},
+    },
 };
-use twilight_util::builder::embed::{EmbedBuilder, EmbedFieldBuilder, ImageSource};
 
-use crate::{Context, utils::to_timestamp};
+use crate::{
+    Context,
+    config::NowPlayingMode,
+    music::{
+        controls, embeds, idle, player, sources::ResolvedTrack, state::LoopMode, track::TrackData,
+    },
+};
+
+const NOW_PLAYING_INTERVAL: Duration = Duration::from_secs(10);
 
 pub struct TrackPlayableHandler {
-    pub user: Id<UserMarker>,
-    pub channel_id: Id<ChannelMarker>,
-    pub metadata: AuxMetadata,
+    pub guild_id: Id<GuildMarker>,
     pub ctx: Context,
 }
 
 #[async_trait]
 impl EventHandler for TrackPlayableHandler {
-    async fn act(&self, _ctx: &EventContext<'_>) -> Option<Event> {
-        let _controls = self
+    async fn act(&self, ctx: &EventContext<'_>) -> Option<Event> {
+        let EventContext::Track([(_, handle)]) = ctx else {
+            return None;
+        };
+        let data = self.ctx.trackdata.get(self.guild_id, handle.uuid())?;
+        let loop_mode = self.ctx.guilds.get(self.guild_id).loop_mode;
+        if loop_mode == LoopMode::Track
+            && let Err(error) = handle.enable_loop()
+        {
+            tracing::warn!(?error, "failed to loop track");
+        }
+
+        let attachments: Vec<_> = data
+            .artwork
+            .iter()
+            .map(|artwork| Attachment::from_bytes(artwork.file_name(), artwork.data.clone(), 0))
+            .collect();
+
+        let message = match self
             .ctx
             .client
-            .create_message(self.channel_id)
-            .embeds(&[EmbedBuilder::new()
-                .color(0xf04628)
-                .title("Now playing")
-                .field(
-                    EmbedFieldBuilder::new(
-                        "Song",
-                        format!(
-                            "[{}]({})",
-                            self.metadata.title.as_ref().unwrap(),
-                            self.metadata.source_url.as_ref().unwrap()
-                        ),
-                    )
-                    .inline(),
-                )
-                .field(
-                    EmbedFieldBuilder::new(
-                        "Duration",
-                        to_timestamp(self.metadata.duration.unwrap().as_secs()),
-                    )
-                    .inline(),
-                )
-                .field(
-                    EmbedFieldBuilder::new("Requested by", format!("{}", self.user.mention()))
-                        .inline(),
-                )
-                .image(ImageSource::url(self.metadata.thumbnail.as_ref().unwrap()).unwrap())
-                .build()])
+            .create_message(data.channel_id)
+            .embeds(&[embeds::now_playing(&data, None, loop_mode).build()])
+            .components(&controls::components_for(&self.ctx, self.guild_id, handle).await)
+            .attachments(&attachments)
+            .await
+        {
+            Ok(response) => response.model().await.map_err(anyhow::Error::from),
+            Err(error) => Err(error.into()),
+        };
+        let message = match message {
+            Ok(message) => message,
+            Err(error) => {
+                tracing::warn!(?error, "failed to post now playing message");
+                return None;
+            }
+        };
+
+        // Static messages still follow the stream title of radio streams.
+        if self.ctx.config.now_playing != NowPlayingMode::Static || data.icy {
+            tokio::spawn(update_now_playing(
+                self.ctx.clone(),
+                self.guild_id,
+                (*handle).clone(),
+                data,
+                message.id,
+            ));
+        }
+
+        None
+    }
+}
+
+/// Refreshes a "Now playing" message until its track is done.
+///
+/// Live messages get their progress bar and buttons refreshed, and are finalized once the
+/// track ends. Static ones are only edited when the stream title of a radio stream changes.
+async fn update_now_playing(
+    ctx: Context,
+    guild_id: Id<GuildMarker>,
+    handle: TrackHandle,
+    data: Arc<TrackData>,
+    message_id: Id<MessageMarker>,
+) {
+    let live = ctx.config.now_playing != NowPlayingMode::Static;
+    let mut shown_title = data.stream_title.read().unwrap().clone();
+    let mut interval = tokio::time::interval(NOW_PLAYING_INTERVAL);
+    interval.tick().await;
+
+    loop {
+        interval.tick().await;
+
+        let (position, paused) = match handle.get_info().await {
+            Ok(state) if !state.playing.is_done() => {
+                (state.position, state.playing == PlayMode::Pause)
+            }
+            _ => break,
+        };
+        let loop_mode = ctx.guilds.get(guild_id).loop_mode;
+        let title = data.stream_title.read().unwrap().clone();
+        if !live && title == shown_title {
+            continue;
+        }
+        shown_title = title;
+
+        let embed = [embeds::now_playing(&data, live.then_some(position), loop_mode).build()];
+        let update = ctx
+            .client
+            .update_message(data.channel_id, message_id)
+            .embeds(Some(&embed));
+        // Live messages refresh their buttons too, as commands change the state they show.
+        let result = match live {
+            true => {
+                update
+                    .components(Some(&controls::components(paused, loop_mode)))
+                    .await
+            }
+            false => update.await,
+        };
+        if let Err(error) = result {
+            tracing::warn!(?error, "failed to update now playing message");
+            return;
+        }
+    }
+
+    if !live {
+        return;
+    }
+
+    let result = match ctx.config.now_playing {
+        NowPlayingMode::Ephemeral => ctx
+            .client
+            .delete_message(data.channel_id, message_id)
+            .await
+            .map(drop),
+        _ => ctx
+            .client
+            .update_message(data.channel_id, message_id)
+            .embeds(Some(&[embeds::now_playing(&data, None, LoopMode::Off)
+                .title("Finished playing")
+                .build()]))
+            .components(Some(&[]))
             .await
-            .unwrap();
+            .map(drop),
+    };
+
+    if let Err(error) = result {
+        tracing::warn!(?error, "failed to finalize now playing message");
+    }
+}
+
+/// Drops the [`TrackData`] of tracks that finished or were stopped, and starts the idle
+/// timer once the queue runs out.
+///
+/// In [`LoopMode::Queue`], tracks that played to the end are queued again. Skipped and
+/// stopped tracks are not, so `/stop` still empties the queue.
+pub struct TrackEndHandler {
+    pub guild_id: Id<GuildMarker>,
+    pub ctx: Context,
+}
+
+#[async_trait]
+impl EventHandler for TrackEndHandler {
+    async fn act(&self, ctx: &EventContext<'_>) -> Option<Event> {
+        if let EventContext::Track(tracks) = ctx {
+            let loop_mode = self.ctx.guilds.get(self.guild_id).loop_mode;
+            for (state, handle) in tracks.iter() {
+                let Some(data) = self.ctx.trackdata.remove(self.guild_id, handle.uuid()) else {
+                    continue;
+                };
+
+                if loop_mode == LoopMode::Queue && state.playing == PlayMode::End {
+                    tokio::spawn(requeue(self.ctx.clone(), self.guild_id, data));
+                }
+            }
+
+            // A timer started because the bot was left alone is not pushed back by tracks
+            // ending, only by the queue running out.
+            let ended: Vec<_> = tracks.iter().map(|(_, handle)| handle.uuid()).collect();
+            if let Some(call_lock) = self.ctx.songbird.get(self.guild_id)
+                && call_lock
+                    .lock()
+                    .await
+                    .queue()
+                    .current_queue()
+                    .iter()
+                    .all(|track| ended.contains(&track.uuid()))
+            {
+                idle::schedule(&self.ctx, self.guild_id);
+            }
+        }
 
         None
     }
 }
+
+/// Queues a track again from its stored metadata, keeping its own volume.
+pub async fn requeue(ctx: Context, guild_id: Id<GuildMarker>, data: Arc<TrackData>) {
+    let input = match ctx.sources.reload(&data.query).await {
+        Ok(input) => input,
+        Err(error) => {
+            tracing::warn!(?error, "failed to queue {} again", data.query);
+            return;
+        }
+    };
+
+    let track = ResolvedTrack {
+        input,
+        metadata: data.metadata.clone(),
+        query: data.query.clone(),
+        artwork: data.artwork.clone(),
+        icy: data.icy,
+    };
+    if let Err(error) = player::enqueue_with_volume(
+        &ctx,
+        guild_id,
+        data.requester,
+        data.channel_id,
+        track,
+        *data.volume.read().unwrap(),
+    )
+    .await
+    {
+        tracing::warn!(?error, "failed to queue {} again", data.query);
+    }
+}
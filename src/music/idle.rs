@@ -0,0 +1,127 @@
+use std::mem;
+
+use songbird::tracks::PlayMode;
+use twilight_model::id::{Id, marker::GuildMarker};
+
+use crate::{
+    Context,
+    music::{controls, voice},
+};
+
+/// Why the bot is no longer needed in a voice channel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Idle {
+    EmptyQueue,
+    Alone,
+}
+
+/// Restarts the idle timer of the guild. Once it runs out, the bot leaves if it is idle.
+pub fn schedule(ctx: &Context, guild_id: Id<GuildMarker>) {
+    let timer = tokio::spawn(expire(ctx.clone(), guild_id)).abort_handle();
+    let previous = ctx
+        .guilds
+        .update(guild_id, |state| state.idle_timer.replace(timer));
+
+    if let Some(previous) = previous {
+        previous.abort();
+    }
+}
+
+/// Starts the idle timer of the guild, unless one is already running.
+fn start(ctx: &Context, guild_id: Id<GuildMarker>) {
+    let running = ctx
+        .guilds
+        .get(guild_id)
+        .idle_timer
+        .is_some_and(|timer| !timer.is_finished());
+    if !running {
+        schedule(ctx, guild_id);
+    }
+}
+
+async fn expire(ctx: Context, guild_id: Id<GuildMarker>) {
+    tokio::time::sleep(ctx.config.idle_timeout).await;
+    // Leaving aborts the timer, which must not be this task anymore.
+    ctx.guilds.update(guild_id, |state| state.idle_timer = None);
+
+    let message = match idle(&ctx, guild_id).await {
+        Some(Idle::EmptyQueue) => "Left the voice channel after the queue ran out.",
+        Some(Idle::Alone) if !ctx.settings.get(guild_id).pause_when_alone => {
+            "Left the voice channel since everyone else did."
+        }
+        _ => return,
+    };
+
+    match controls::leave(&ctx, guild_id).await {
+        Ok(()) => voice::notify(&ctx, guild_id, message).await,
+        Err(error) => tracing::warn!(?error, "failed to leave idle voice channel"),
+    }
+}
+
+async fn idle(ctx: &Context, guild_id: Id<GuildMarker>) -> Option<Idle> {
+    let call_lock = ctx.songbird.get(guild_id)?;
+    if call_lock.lock().await.queue().is_empty() {
+        return Some(Idle::EmptyQueue);
+    }
+
+    voice::listeners(ctx, guild_id)
+        .await
+        .is_empty()
+        .then_some(Idle::Alone)
+}
+
+/// Reacts to a voice state change in the guild, in case it left the bot alone or brought
+/// listeners back. Other changes, such as mutes or moves between other channels, are ignored.
+///
+/// A guild that prefers it has playback paused while nobody listens, and resumed once
+/// someone returns. Otherwise the bot leaves when its idle timer runs out.
+pub async fn voice_state_update(ctx: Context, guild_id: Id<GuildMarker>) {
+    if ctx.songbird.get(guild_id).is_none() {
+        return;
+    }
+
+    let alone = voice::listeners(&ctx, guild_id).await.is_empty();
+    let (was_alone, paused_alone) = ctx.guilds.update(guild_id, |state| {
+        (mem::replace(&mut state.alone, alone), state.paused_alone)
+    });
+    if alone == was_alone {
+        return;
+    }
+
+    match (alone, paused_alone) {
+        (true, false) if ctx.settings.get(guild_id).pause_when_alone => {
+            // Tracks paused by a listener stay paused when they come back.
+            let Some(handle) = controls::current(&ctx, guild_id).await else {
+                return start(&ctx, guild_id);
+            };
+            if !matches!(handle.get_info().await, Ok(state) if state.playing == PlayMode::Play) {
+                return;
+            }
+
+            match handle.pause() {
+                Ok(()) => {
+                    ctx.guilds
+                        .update(guild_id, |state| state.paused_alone = true);
+                    voice::notify(
+                        &ctx,
+                        guild_id,
+                        "Paused since everyone left the voice channel.",
+                    )
+                    .await;
+                }
+                Err(error) => tracing::warn!(?error, "failed to pause for empty channel"),
+            }
+        }
+        (true, false) => start(&ctx, guild_id),
+        (false, true) => {
+            ctx.guilds
+                .update(guild_id, |state| state.paused_alone = false);
+            if let Some(handle) = controls::current(&ctx, guild_id).await
+                && let Err(error) = handle.play()
+            {
+                tracing::warn!(?error, "failed to resume after listeners returned");
+            }
+        }
+        _ => {}
+    }
+}
@@ -1,4 +1,14 @@
 pub mod commands;
+pub mod controls;
+pub mod embeds;
 pub mod events;
+pub mod idle;
+pub mod player;
+pub mod recovery;
+pub mod sources;
+pub mod state;
+pub mod suggestions;
+pub mod track;
+pub mod voice;
 
 pub use commands::*;
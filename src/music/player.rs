@@ -0,0 +1,211 @@
+use std::{
+    sync::RwLock,
+    time::{Duration, SystemTime},
+};
+
+use anyhow::{Context as _, bail};
+use songbird::{
+    CoreEvent, Event, TrackEvent,
+    input::AuxMetadata,
+    tracks::{PlayMode, Track},
+};
+use twilight_model::{
+    application::interaction::Interaction,
+    id::{
+        Id,
+        marker::{ChannelMarker, GuildMarker, UserMarker},
+    },
+};
+use uuid::Uuid;
+
+use crate::{
+    Context,
+    error::CommandError,
+    music::{
+        controls, embeds,
+        events::{TrackEndHandler, TrackPlayableHandler},
+        idle,
+        recovery::RecoveryHandler,
+        sources::{ResolvedTrack, icy},
+        track::TrackData,
+        voice,
+    },
+};
+
+/// How long before the end of a track the next one starts loading.
+const PRELOAD_TIME: Duration = Duration::from_secs(5);
+
+/// Joins the voice channel of the user who invoked `interaction`, unless already there.
+///
+/// An idle bot follows the user into their channel, but one playing music elsewhere stays.
+pub async fn join(ctx: &Context, interaction: &Interaction) -> anyhow::Result<()> {
+    let guild_id = interaction.guild_id.ok_or(CommandError::NotInGuild)?;
+    let requester = interaction.author().unwrap().id;
+
+    let Some(channel_id) = voice::user_channel(ctx, guild_id, requester) else {
+        bail!(CommandError::NotInVoice);
+    };
+
+    match voice::bot_channel(ctx, guild_id).await {
+        Some(current) if current == channel_id => return Ok(()),
+        Some(current) if controls::current(ctx, guild_id).await.is_some() => {
+            bail!(CommandError::NotInBotChannel(current))
+        }
+        _ => {}
+    }
+
+    connect(ctx, guild_id, channel_id).await
+}
+
+/// Connects to `channel_id`, moving over from the current voice channel if there is one.
+///
+/// The queue stays as it is. The current track is held while moving, so it continues where
+/// it left off.
+pub async fn connect(
+    ctx: &Context,
+    guild_id: Id<GuildMarker>,
+    channel_id: Id<ChannelMarker>,
+) -> anyhow::Result<()> {
+    if voice::bot_channel(ctx, guild_id).await == Some(channel_id) {
+        return Ok(());
+    }
+
+    let mut held = None;
+    if let Some(handle) = controls::current(ctx, guild_id).await
+        && matches!(handle.get_info().await, Ok(state) if state.playing == PlayMode::Play)
+    {
+        handle.pause()?;
+        held = Some(handle);
+    }
+
+    // Each call gets its own recovery handler, which goes away with the call.
+    if ctx.songbird.get(guild_id).is_none() {
+        let call_lock = ctx.songbird.get_or_insert(guild_id);
+        let mut call = call_lock.lock().await;
+        for event in [CoreEvent::DriverDisconnect, CoreEvent::DriverReconnect] {
+            call.add_global_event(
+                Event::Core(event),
+                RecoveryHandler {
+                    guild_id,
+                    ctx: ctx.clone(),
+                },
+            );
+        }
+    }
+
+    tracing::debug!("joining voice channel {} in guild {}", channel_id, guild_id);
+
+    let joined = ctx
+        .songbird
+        .join(guild_id, channel_id)
+        .await
+        .context("failed to join voice channel");
+    if let Some(handle) = held {
+        handle.play()?;
+    }
+    joined?;
+
+    // Leaves again if nothing ends up queued.
+    idle::schedule(ctx, guild_id);
+
+    Ok(())
+}
+
+/// Adds a resolved track to the guild queue, returning its metadata.
+///
+/// The "Now playing" message of the track is posted in `channel_id`.
+pub async fn enqueue(
+    ctx: &Context,
+    guild_id: Id<GuildMarker>,
+    requester: Id<UserMarker>,
+    channel_id: Id<ChannelMarker>,
+    track: ResolvedTrack,
+) -> anyhow::Result<AuxMetadata> {
+    enqueue_with_volume(ctx, guild_id, requester, channel_id, track, None).await
+}
+
+/// Like [`enqueue`], but with a volume in percent for this track only, overriding the
+/// guild volume.
+pub async fn enqueue_with_volume(
+    ctx: &Context,
+    guild_id: Id<GuildMarker>,
+    requester: Id<UserMarker>,
+    channel_id: Id<ChannelMarker>,
+    track: ResolvedTrack,
+    track_volume: Option<u16>,
+) -> anyhow::Result<AuxMetadata> {
+    let ResolvedTrack {
+        input,
+        metadata,
+        query,
+        artwork,
+        icy,
+    } = track;
+
+    let Some(call_lock) = ctx.songbird.get(guild_id) else {
+        bail!("Bami is not in a voice channel");
+    };
+
+    let uuid = Uuid::new_v4();
+    let data = ctx.trackdata.insert(
+        guild_id,
+        uuid,
+        TrackData {
+            requester,
+            channel_id,
+            metadata: metadata.clone(),
+            queued_at: SystemTime::now(),
+            query,
+            artwork,
+            icy,
+            stream_title: Default::default(),
+            volume: RwLock::new(track_volume),
+        },
+    );
+
+    let track;
+    {
+        let mut call = call_lock.lock().await;
+        track = call.enqueue_with_preload(
+            Track::new_with_uuid(input, uuid).volume(volume(
+                track_volume.unwrap_or_else(|| ctx.settings.get(guild_id).volume),
+            )),
+            metadata
+                .duration
+                .map(|duration| duration.saturating_sub(PRELOAD_TIME)),
+        );
+    }
+
+    track.add_event(
+        Event::Track(TrackEvent::Playable),
+        TrackPlayableHandler {
+            guild_id,
+            ctx: ctx.clone(),
+        },
+    )?;
+    track.add_event(
+        Event::Track(TrackEvent::End),
+        TrackEndHandler {
+            guild_id,
+            ctx: ctx.clone(),
+        },
+    )?;
+
+    if icy {
+        tokio::spawn(icy::watch(
+            ctx.http.clone(),
+            data.query.clone(),
+            track,
+            data,
+        ));
+    }
+
+    tracing::info!("Queued track {}", embeds::title(&metadata));
+
+    Ok(metadata)
+}
+
+/// Converts a volume in percent to the multiplier songbird expects.
+pub fn volume(percent: u16) -> f32 {
+    f32::from(percent) / 100.0
+}
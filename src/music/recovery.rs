@@ -0,0 +1,181 @@
+use std::{mem, sync::Arc, time::Duration};
+
+use async_trait::async_trait;
+use songbird::{
+    Event, EventContext, EventHandler,
+    events::context_data::{DisconnectKind, DisconnectReason},
+    model::CloseCode,
+};
+use twilight_model::id::{
+    Id,
+    marker::{ChannelMarker, GuildMarker},
+};
+
+use crate::{
+    Context,
+    music::{controls, events, track::TrackData, voice},
+};
+
+/// How often rejoining is attempted before giving up.
+const ATTEMPTS: u32 = 5;
+/// Wait before the first attempt, doubled after each failed one.
+const FIRST_BACKOFF: Duration = Duration::from_secs(2);
+
+/// Rejoins the voice channel when Discord drops the connection of a call for good.
+///
+/// Registered once per call, for both disconnects and reconnects of its driver.
+pub struct RecoveryHandler {
+    pub guild_id: Id<GuildMarker>,
+    pub ctx: Context,
+}
+
+#[async_trait]
+impl EventHandler for RecoveryHandler {
+    async fn act(&self, ctx: &EventContext<'_>) -> Option<Event> {
+        match ctx {
+            // Disconnects without a reason were asked for, and failed first connections are
+            // reported by the join itself.
+            EventContext::DriverDisconnect(data)
+                if data.reason.is_some() && !matches!(data.kind, DisconnectKind::Connect) =>
+            {
+                // Kicked by a moderator, or the channel was deleted, so staying out is right.
+                if matches!(
+                    data.reason,
+                    Some(DisconnectReason::WsClosed(Some(CloseCode::Disconnected)))
+                ) {
+                    tracing::info!("removed from voice channel in guild {}", self.guild_id);
+                    return None;
+                }
+
+                tracing::warn!(
+                    reason = ?data.reason,
+                    "voice connection in guild {} dropped",
+                    self.guild_id
+                );
+
+                let channel_id = data.channel_id?;
+                tokio::spawn(recover(
+                    self.ctx.clone(),
+                    self.guild_id,
+                    Id::from(channel_id.0),
+                ));
+            }
+            EventContext::DriverReconnect(_) => {
+                tracing::info!("voice connection in guild {} restored", self.guild_id);
+                self.ctx
+                    .guilds
+                    .update(self.guild_id, |state| state.recovering = false);
+            }
+            _ => {}
+        }
+
+        None
+    }
+}
+
+/// Rejoins `channel_id` with growing delays, leaving for good once every attempt failed.
+async fn recover(ctx: Context, guild_id: Id<GuildMarker>, channel_id: Id<ChannelMarker>) {
+    if ctx
+        .guilds
+        .update(guild_id, |state| mem::replace(&mut state.recovering, true))
+    {
+        return;
+    }
+
+    // Taken right away, as the queue may not survive the connection.
+    let (tracks, position) = snapshot(&ctx, guild_id).await;
+
+    let mut backoff = FIRST_BACKOFF;
+    for attempt in 1..=ATTEMPTS {
+        tokio::time::sleep(backoff).await;
+        backoff *= 2;
+
+        // The bot left meanwhile, or the driver got the connection back by itself.
+        if ctx.songbird.get(guild_id).is_none() || !ctx.guilds.get(guild_id).recovering {
+            return;
+        }
+
+        match ctx.songbird.join(guild_id, channel_id).await {
+            Ok(_) => {
+                tracing::info!(
+                    "rejoined voice channel {} in guild {}",
+                    channel_id,
+                    guild_id
+                );
+                restore(&ctx, guild_id, tracks, position).await;
+                ctx.guilds
+                    .update(guild_id, |state| state.recovering = false);
+                return;
+            }
+            Err(error) => tracing::warn!(
+                ?error,
+                "attempt {} of {} to rejoin voice channel {} failed",
+                attempt,
+                ATTEMPTS,
+                channel_id
+            ),
+        }
+    }
+
+    ctx.guilds
+        .update(guild_id, |state| state.recovering = false);
+    if let Err(error) = controls::leave(&ctx, guild_id).await {
+        tracing::warn!(?error, "failed to leave after losing the voice connection");
+    }
+    voice::notify(
+        &ctx,
+        guild_id,
+        "Lost the voice connection and could not get it back, so the queue was dropped.",
+    )
+    .await;
+}
+
+/// The queued tracks of the guild, and the position of the current one.
+async fn snapshot(
+    ctx: &Context,
+    guild_id: Id<GuildMarker>,
+) -> (Vec<Arc<TrackData>>, Option<Duration>) {
+    let Some(call_lock) = ctx.songbird.get(guild_id) else {
+        return (Vec::new(), None);
+    };
+    let handles = call_lock.lock().await.queue().current_queue();
+
+    let position = match handles.first() {
+        Some(handle) => handle.get_info().await.ok().map(|state| state.position),
+        None => None,
+    };
+    let tracks = handles
+        .iter()
+        .filter_map(|handle| ctx.trackdata.get(guild_id, handle.uuid()))
+        .collect();
+
+    (tracks, position)
+}
+
+/// Queues `tracks` again if the queue was lost with the connection, continuing the first
+/// one from `position`.
+async fn restore(
+    ctx: &Context,
+    guild_id: Id<GuildMarker>,
+    tracks: Vec<Arc<TrackData>>,
+    position: Option<Duration>,
+) {
+    let Some(call_lock) = ctx.songbird.get(guild_id) else {
+        return;
+    };
+    // A driver that kept its queue also kept the position of the current track.
+    if !call_lock.lock().await.queue().is_empty() {
+        return;
+    }
+
+    for data in tracks {
+        events::requeue(ctx.clone(), guild_id, data).await;
+    }
+
+    if let Some(position) = position
+        && let Some(handle) = controls::current(ctx, guild_id).await
+        && let Err(error) = handle.seek_async(position).await
+    {
+        tracing::debug!(?error, "failed to restore position after rejoining");
+    }
+}
@@ -0,0 +1,269 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use anyhow::{Context as _, bail};
+use async_trait::async_trait;
+use songbird::input::{AuxMetadata, File, Input};
+use symphonia::core::{
+    formats::FormatOptions,
+    io::MediaSourceStream,
+    meta::{MetadataOptions, MetadataRevision, StandardTagKey},
+    probe::Hint,
+};
+use tokio::sync::mpsc;
+
+use crate::music::{
+    sources::{ResolvedTrack, SourceResolver},
+    track::Artwork,
+};
+
+const AUDIO_EXTENSIONS: &[&str] = &["aac", "flac", "m4a", "mp3", "oga", "ogg", "opus", "wav"];
+
+/// Plays `file:` queries from the media library on the bot host.
+pub struct LocalFile {
+    root: PathBuf,
+}
+
+impl LocalFile {
+    pub fn new(root: PathBuf) -> Self {
+        Self { root }
+    }
+
+    /// Resolves `relative` inside the library, refusing paths that escape it.
+    async fn path(&self, relative: &str) -> anyhow::Result<PathBuf> {
+        let root = tokio::fs::canonicalize(&self.root)
+            .await
+            .context("media library is not accessible")?;
+        let path = tokio::fs::canonicalize(root.join(relative.trim_start_matches('/')))
+            .await
+            .with_context(|| format!("file not found: {}", relative))?;
+
+        if !path.starts_with(&root) {
+            bail!("{} is outside the media library", relative);
+        }
+
+        Ok(path)
+    }
+}
+
+#[async_trait]
+impl SourceResolver for LocalFile {
+    async fn matches(&self, query: &str) -> bool {
+        query.starts_with("file:")
+    }
+
+    async fn resolve(
+        &self,
+        query: &str,
+        tracks: mpsc::Sender<ResolvedTrack>,
+    ) -> anyhow::Result<()> {
+        let relative = query.trim_start_matches("file:");
+        let path = self.path(relative).await?;
+
+        let (metadata, artwork) = tokio::task::spawn_blocking({
+            let path = path.clone();
+            move || read_tags(&path)
+        })
+        .await?;
+
+        let track = ResolvedTrack {
+            input: File::new(path).into(),
+            metadata,
+            query: query.to_owned(),
+            artwork,
+            icy: false,
+        };
+        _ = tracks.send(track).await;
+
+        Ok(())
+    }
+
+    async fn reload(&self, query: &str) -> anyhow::Result<Input> {
+        let path = self.path(query.trim_start_matches("file:")).await?;
+        Ok(File::new(path).into())
+    }
+}
+
+/// How long a listing of the media library is reused for autocomplete.
+const LISTING_TTL: Duration = Duration::from_secs(60);
+/// Files beyond this many are left out of the listing, to bound the memory it takes.
+const LISTING_SIZE: usize = 10_000;
+
+/// The audio files of the media library, listed once in a while for autocomplete.
+pub struct Library {
+    root: PathBuf,
+    /// Held across the walk, so keystrokes arriving meanwhile wait for it instead of walking
+    /// the library again.
+    listing: tokio::sync::Mutex<Option<Listing>>,
+}
+
+struct Listing {
+    listed_at: Instant,
+    paths: Arc<Vec<String>>,
+}
+
+impl Library {
+    pub fn new(root: PathBuf) -> Self {
+        Self {
+            root,
+            listing: Default::default(),
+        }
+    }
+
+    /// Audio files whose path relative to the root contains `filter`, at most `limit` of them.
+    pub async fn search(&self, filter: &str, limit: usize) -> anyhow::Result<Vec<String>> {
+        let filter = filter.to_lowercase();
+
+        Ok(self
+            .listing()
+            .await?
+            .iter()
+            .filter(|path| path.to_lowercase().contains(&filter))
+            .take(limit)
+            .cloned()
+            .collect())
+    }
+
+    async fn listing(&self) -> anyhow::Result<Arc<Vec<String>>> {
+        let mut listing = self.listing.lock().await;
+        if let Some(listing) = listing.as_ref()
+            && listing.listed_at.elapsed() < LISTING_TTL
+        {
+            return Ok(listing.paths.clone());
+        }
+
+        let root = self.root.clone();
+        let paths = Arc::new(tokio::task::spawn_blocking(move || list(&root)).await??);
+        *listing = Some(Listing {
+            listed_at: Instant::now(),
+            paths: paths.clone(),
+        });
+
+        Ok(paths)
+    }
+}
+
+/// Lists the audio files under `root`, relative to it.
+///
+/// Symlinked directories are not followed, so a link back up the tree cannot make the walk
+/// go on forever.
+fn list(root: &Path) -> anyhow::Result<Vec<String>> {
+    let mut found = Vec::new();
+    let mut directories = vec![root.to_owned()];
+
+    while let Some(directory) = directories.pop() {
+        let mut entries: Vec<_> = fs::read_dir(&directory)?
+            .filter_map(Result::ok)
+            .filter_map(|entry| Some((entry.path(), entry.file_type().ok()?)))
+            .collect();
+        entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+        for (path, file_type) in entries {
+            if file_type.is_dir() {
+                directories.push(path);
+                continue;
+            }
+            if !is_audio_file(&path) {
+                continue;
+            }
+
+            let Ok(relative) = path.strip_prefix(root) else {
+                continue;
+            };
+            found.push(relative.to_string_lossy().into_owned());
+            if found.len() >= LISTING_SIZE {
+                return Ok(found);
+            }
+        }
+    }
+
+    Ok(found)
+}
+
+fn is_audio_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| {
+            AUDIO_EXTENSIONS.contains(&extension.to_ascii_lowercase().as_str())
+        })
+}
+
+/// Reads the title, artist, album, duration and cover art of `path`.
+///
+/// Files without tags, or that symphonia cannot probe, are named after the file itself.
+fn read_tags(path: &Path) -> (AuxMetadata, Option<Artwork>) {
+    let mut metadata = AuxMetadata {
+        title: path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned()),
+        ..Default::default()
+    };
+
+    let artwork = match probe_tags(path, &mut metadata) {
+        Ok(artwork) => artwork,
+        Err(error) => {
+            tracing::warn!(?error, "failed to read tags of {}", path.display());
+            None
+        }
+    };
+
+    (metadata, artwork)
+}
+
+fn probe_tags(path: &Path, metadata: &mut AuxMetadata) -> anyhow::Result<Option<Artwork>> {
+    let source = MediaSourceStream::new(Box::new(fs::File::open(path)?), Default::default());
+    let mut hint = Hint::new();
+    if let Some(extension) = path.extension().and_then(|extension| extension.to_str()) {
+        hint.with_extension(extension);
+    }
+
+    let mut probed = symphonia::default::get_probe().format(
+        &hint,
+        source,
+        &FormatOptions::default(),
+        &MetadataOptions::default(),
+    )?;
+
+    if let Some(track) = probed.format.default_track() {
+        let params = &track.codec_params;
+        if let (Some(time_base), Some(frames)) = (params.time_base, params.n_frames) {
+            let time = time_base.calc_time(frames);
+            metadata.duration = Some(Duration::from_secs_f64(time.seconds as f64 + time.frac));
+        }
+        metadata.sample_rate = params.sample_rate;
+        metadata.channels = params.channels.map(|channels| channels.count() as u8);
+    }
+
+    // Tags can sit in front of the container (e.g. ID3) or inside it.
+    let mut artwork = None;
+    if let Some(revision) = probed.metadata.get().as_ref().and_then(|m| m.current()) {
+        artwork = apply_revision(revision, metadata);
+    }
+    if let Some(revision) = probed.format.metadata().current() {
+        artwork = apply_revision(revision, metadata).or(artwork);
+    }
+
+    Ok(artwork)
+}
+
+fn apply_revision(revision: &MetadataRevision, metadata: &mut AuxMetadata) -> Option<Artwork> {
+    for tag in revision.tags() {
+        let value = Some(tag.value.to_string());
+        match tag.std_key {
+            Some(StandardTagKey::TrackTitle) => metadata.title = value,
+            Some(StandardTagKey::Artist) => metadata.artist = value,
+            Some(StandardTagKey::Album) => metadata.album = value,
+            Some(StandardTagKey::Date) => metadata.date = value,
+            _ => {}
+        }
+    }
+
+    revision.visuals().first().map(|visual| Artwork {
+        media_type: visual.media_type.clone(),
+        data: visual.data.to_vec(),
+    })
+}
@@ -0,0 +1,284 @@
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+use reqwest::header::{CONTENT_TYPE, HeaderMap};
+use songbird::input::{AuxMetadata, HttpRequest, Input};
+use tokio::sync::mpsc;
+
+use crate::music::sources::{ResolvedTrack, SourceResolver, icy, is_http_url};
+
+const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+/// How long probed headers are reused, so that matching, resolving and reloading a URL
+/// only probe it once.
+const PROBE_TTL: Duration = Duration::from_secs(10 * 60);
+const PROBE_CACHE_SIZE: usize = 256;
+
+/// Playlist formats served with audio content types, which only yt-dlp can follow.
+const PLAYLIST_TYPES: &[&str] = &[
+    "audio/mpegurl",
+    "audio/x-mpegurl",
+    "audio/x-scpls",
+    "audio/x-ms-wax",
+];
+
+/// Plays URLs serving audio directly, such as files or Icecast/Shoutcast radio streams,
+/// without going through yt-dlp.
+pub struct HttpAudio {
+    client: reqwest::Client,
+    probes: Mutex<HashMap<String, Probe>>,
+}
+
+struct Probe {
+    probed_at: Instant,
+    headers: HeaderMap,
+}
+
+impl HttpAudio {
+    pub fn new(client: reqwest::Client) -> Self {
+        Self {
+            client,
+            probes: Default::default(),
+        }
+    }
+
+    /// The response headers of `url`, probed once and then cached for a while.
+    async fn probe(&self, url: &str) -> Option<HeaderMap> {
+        if let Some(headers) = self.cached(url) {
+            return Some(headers);
+        }
+
+        // Failed probes are not cached, as the server may be back for the next attempt.
+        let headers = self.fetch_headers(url).await?;
+
+        let mut probes = self.probes.lock().unwrap();
+        probes.retain(|_, probe| probe.probed_at.elapsed() < PROBE_TTL);
+        if probes.len() < PROBE_CACHE_SIZE {
+            probes.insert(
+                url.to_owned(),
+                Probe {
+                    probed_at: Instant::now(),
+                    headers: headers.clone(),
+                },
+            );
+        }
+
+        Some(headers)
+    }
+
+    fn cached(&self, url: &str) -> Option<HeaderMap> {
+        self.probes
+            .lock()
+            .unwrap()
+            .get(url)
+            .filter(|probe| probe.probed_at.elapsed() < PROBE_TTL)
+            .map(|probe| probe.headers.clone())
+    }
+
+    /// Fetches the response headers of `url`, dropping the connection before the body.
+    ///
+    /// ICY metadata is requested so that radio servers announce it in the headers.
+    async fn fetch_headers(&self, url: &str) -> Option<HeaderMap> {
+        let request = self
+            .client
+            .get(url)
+            .header(icy::METADATA_HEADER, "1")
+            .send();
+
+        match tokio::time::timeout(PROBE_TIMEOUT, request).await {
+            Ok(Ok(response)) if response.status().is_success() => Some(response.headers().clone()),
+            Ok(Ok(response)) => {
+                tracing::debug!("probing {} returned {}", url, response.status());
+                None
+            }
+            Ok(Err(error)) => {
+                tracing::debug!(?error, "failed to probe {}", url);
+                None
+            }
+            Err(_) => {
+                tracing::debug!("probing {} timed out", url);
+                None
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl SourceResolver for HttpAudio {
+    async fn matches(&self, query: &str) -> bool {
+        if !is_http_url(query) {
+            return false;
+        }
+
+        self.probe(query)
+            .await
+            .is_some_and(|headers| is_audio(&headers))
+    }
+
+    async fn resolve(
+        &self,
+        query: &str,
+        tracks: mpsc::Sender<ResolvedTrack>,
+    ) -> anyhow::Result<()> {
+        let headers = self.probe(query).await.unwrap_or_default();
+        let icy = icy::metaint(&headers).is_some();
+
+        let path = query.split(['?', '#']).next().unwrap_or_default();
+        let title = match icy::name(&headers) {
+            Some(name) => name,
+            None => path.rsplit('/').next().unwrap_or(query).to_owned(),
+        };
+
+        let track = ResolvedTrack {
+            input: HttpRequest::new(self.client.clone(), query.to_owned()).into(),
+            metadata: AuxMetadata {
+                title: Some(title),
+                source_url: Some(query.to_owned()),
+                ..Default::default()
+            },
+            query: query.to_owned(),
+            artwork: None,
+            icy,
+        };
+        _ = tracks.send(track).await;
+
+        Ok(())
+    }
+
+    async fn reload(&self, query: &str) -> anyhow::Result<Input> {
+        Ok(HttpRequest::new(self.client.clone(), query.to_owned()).into())
+    }
+}
+
+fn is_audio(headers: &HeaderMap) -> bool {
+    if icy::metaint(headers).is_some() {
+        return true;
+    }
+
+    let Some(content_type) = headers
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+    else {
+        return false;
+    };
+    let content_type = content_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase();
+
+    if PLAYLIST_TYPES.contains(&content_type.as_str()) {
+        return false;
+    }
+
+    content_type.starts_with("audio/") || content_type == "application/ogg"
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    };
+
+    use reqwest::header::HeaderValue;
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpListener,
+    };
+
+    use super::*;
+
+    fn headers(pairs: &[(&'static str, &'static str)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, value) in pairs {
+            headers.insert(*name, HeaderValue::from_static(*value));
+        }
+        headers
+    }
+
+    #[test]
+    fn detects_audio_content_types() {
+        assert!(is_audio(&headers(&[("content-type", "audio/mpeg")])));
+        assert!(is_audio(&headers(&[(
+            "content-type",
+            "Audio/AAC; charset=x"
+        )])));
+        assert!(is_audio(&headers(&[("content-type", "application/ogg")])));
+        assert!(is_audio(&headers(&[
+            ("content-type", "text/html"),
+            ("icy-metaint", "16000"),
+        ])));
+
+        assert!(!is_audio(&headers(&[("content-type", "text/html")])));
+        assert!(!is_audio(&headers(&[("content-type", "audio/x-mpegurl")])));
+        assert!(!is_audio(&headers(&[("content-type", "audio/x-scpls")])));
+        assert!(!is_audio(&HeaderMap::new()));
+    }
+
+    /// Serves `body` as an MP3 file to every request, counting the requests.
+    async fn serve(body: &'static [u8]) -> (String, Arc<AtomicUsize>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/music/song.mp3", listener.local_addr().unwrap());
+        let requests = Arc::new(AtomicUsize::new(0));
+
+        tokio::spawn({
+            let requests = requests.clone();
+            async move {
+                while let Ok((mut stream, _)) = listener.accept().await {
+                    requests.fetch_add(1, Ordering::SeqCst);
+                    let mut request = Vec::new();
+                    let mut buffer = [0; 1024];
+                    while !request.ends_with(b"\r\n\r\n") {
+                        match stream.read(&mut buffer).await {
+                            Ok(0) | Err(_) => break,
+                            Ok(read) => request.extend_from_slice(&buffer[..read]),
+                        }
+                    }
+
+                    let head = format!(
+                        "HTTP/1.1 200 OK\r\n\
+                         Content-Type: audio/mpeg\r\n\
+                         Content-Length: {}\r\n\
+                         Connection: close\r\n\r\n",
+                        body.len()
+                    );
+                    _ = stream.write_all(head.as_bytes()).await;
+                    _ = stream.write_all(body).await;
+                }
+            }
+        });
+
+        (url, requests)
+    }
+
+    #[tokio::test]
+    async fn resolves_served_file_with_one_probe() {
+        let (url, requests) = serve(b"ID3 not really audio").await;
+        let source = HttpAudio::new(reqwest::Client::new());
+
+        assert!(source.matches(&url).await);
+
+        let (sender, mut tracks) = mpsc::channel(1);
+        source.resolve(&url, sender).await.unwrap();
+        let track = tracks.recv().await.unwrap();
+
+        assert_eq!(track.metadata.title.as_deref(), Some("song.mp3"));
+        assert_eq!(track.metadata.source_url.as_deref(), Some(url.as_str()));
+        assert_eq!(track.query, url);
+        assert!(!track.icy);
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn does_not_match_other_urls() {
+        let source = HttpAudio::new(reqwest::Client::new());
+
+        assert!(!source.matches("song.mp3").await);
+        assert!(!source.matches("file:song.mp3").await);
+    }
+}
@@ -0,0 +1,196 @@
+//! ICY metadata, which Icecast and Shoutcast servers interleave with the audio of a
+//! radio stream to announce what is currently playing.
+
+use std::{sync::Arc, time::Duration};
+
+use anyhow::bail;
+use reqwest::header::HeaderMap;
+use songbird::tracks::{PlayMode, TrackHandle};
+
+use crate::music::track::TrackData;
+
+/// Request header asking the server to interleave metadata with the audio.
+pub const METADATA_HEADER: &str = "Icy-MetaData";
+
+/// How often the stream title of a playing stream is refreshed.
+const POLL_INTERVAL: Duration = Duration::from_secs(30);
+const READ_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Servers interleave metadata every few kilobytes; refuse to buffer more than this.
+const MAX_METAINT: usize = 1 << 20;
+
+/// The number of audio bytes between two metadata blocks, if the server sends metadata.
+pub fn metaint(headers: &HeaderMap) -> Option<usize> {
+    headers
+        .get("icy-metaint")?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse()
+        .ok()
+        .filter(|&metaint| metaint > 0)
+}
+
+/// The station name announced by the server.
+pub fn name(headers: &HeaderMap) -> Option<String> {
+    let name = headers.get("icy-name")?.to_str().ok()?.trim();
+    (!name.is_empty()).then(|| name.to_owned())
+}
+
+/// Keeps [`TrackData::stream_title`] up to date while the stream at `url` plays.
+///
+/// The title is read from a separate short-lived connection, so the audio given to
+/// songbird never contains metadata blocks.
+pub async fn watch(
+    client: reqwest::Client,
+    url: String,
+    handle: TrackHandle,
+    data: Arc<TrackData>,
+) {
+    let mut interval = tokio::time::interval(POLL_INTERVAL);
+
+    loop {
+        interval.tick().await;
+
+        match handle.get_info().await {
+            Ok(state) if state.playing.is_done() => break,
+            Ok(state) if state.playing != PlayMode::Play => continue,
+            Ok(_) => {}
+            Err(_) => break,
+        }
+
+        match tokio::time::timeout(READ_TIMEOUT, stream_title(&client, &url)).await {
+            Ok(Ok(Some(title))) => *data.stream_title.write().unwrap() = Some(title),
+            Ok(Ok(None)) => {}
+            Ok(Err(error)) => tracing::debug!(?error, "failed to read stream title of {}", url),
+            Err(_) => tracing::debug!("reading stream title of {} timed out", url),
+        }
+    }
+}
+
+/// Reads the first metadata block of the stream at `url` and returns its `StreamTitle`.
+pub async fn stream_title(client: &reqwest::Client, url: &str) -> anyhow::Result<Option<String>> {
+    let mut response = client
+        .get(url)
+        .header(METADATA_HEADER, "1")
+        .send()
+        .await?
+        .error_for_status()?;
+
+    let Some(metaint) = metaint(response.headers()) else {
+        bail!("{} does not send ICY metadata", url);
+    };
+    if metaint > MAX_METAINT {
+        bail!("metadata interval of {} is too large: {}", url, metaint);
+    }
+
+    // The first block follows `metaint` bytes of audio and a byte giving its length
+    // in multiples of 16 bytes.
+    let mut buffer = Vec::with_capacity(metaint + 1);
+    while buffer.len() <= metaint {
+        read_chunk(&mut response, &mut buffer).await?;
+    }
+    let end = metaint + 1 + buffer[metaint] as usize * 16;
+    while buffer.len() < end {
+        read_chunk(&mut response, &mut buffer).await?;
+    }
+
+    Ok(parse_stream_title(&buffer[metaint + 1..end]))
+}
+
+async fn read_chunk(response: &mut reqwest::Response, buffer: &mut Vec<u8>) -> anyhow::Result<()> {
+    match response.chunk().await? {
+        Some(chunk) => {
+            buffer.extend_from_slice(&chunk);
+            Ok(())
+        }
+        None => bail!("stream ended before its metadata"),
+    }
+}
+
+/// Extracts the title from a metadata block like `StreamTitle='Artist - Song';StreamUrl='';`.
+fn parse_stream_title(block: &[u8]) -> Option<String> {
+    let block = String::from_utf8_lossy(block);
+    let block = block.trim_end_matches('\0');
+
+    let start = block.find("StreamTitle='")? + "StreamTitle='".len();
+    let rest = &block[start..];
+    // Titles can contain quotes, so only a quote followed by `;` ends the value.
+    let end = rest.find("';").unwrap_or(rest.trim_end_matches('\'').len());
+    let title = rest[..end].trim();
+
+    (!title.is_empty()).then(|| title.to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest::header::HeaderValue;
+
+    use super::*;
+
+    #[test]
+    fn parses_stream_titles() {
+        assert_eq!(
+            parse_stream_title(b"StreamTitle='Artist - Song';StreamUrl='';"),
+            Some("Artist - Song".to_owned())
+        );
+        assert_eq!(
+            parse_stream_title(b"StreamUrl='x';StreamTitle='Song';"),
+            Some("Song".to_owned())
+        );
+    }
+
+    #[test]
+    fn keeps_quotes_inside_titles() {
+        assert_eq!(
+            parse_stream_title(b"StreamTitle='Guns N' Roses - Don't Cry';"),
+            Some("Guns N' Roses - Don't Cry".to_owned())
+        );
+    }
+
+    #[test]
+    fn parses_titles_without_terminator() {
+        assert_eq!(
+            parse_stream_title(b"StreamTitle='Artist - Song'"),
+            Some("Artist - Song".to_owned())
+        );
+        assert_eq!(
+            parse_stream_title(b"StreamTitle='Artist - Song"),
+            Some("Artist - Song".to_owned())
+        );
+    }
+
+    #[test]
+    fn strips_nul_padding() {
+        let mut block = b"StreamTitle='Song';".to_vec();
+        block.resize(32, 0);
+        assert_eq!(parse_stream_title(&block), Some("Song".to_owned()));
+
+        let mut block = b"StreamTitle='Song'".to_vec();
+        block.resize(32, 0);
+        assert_eq!(parse_stream_title(&block), Some("Song".to_owned()));
+    }
+
+    #[test]
+    fn rejects_blocks_without_title() {
+        assert_eq!(parse_stream_title(b""), None);
+        assert_eq!(parse_stream_title(&[0; 16]), None);
+        assert_eq!(parse_stream_title(b"StreamTitle='';"), None);
+        assert_eq!(parse_stream_title(b"StreamUrl='x';"), None);
+    }
+
+    #[test]
+    fn reads_metaint() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(metaint(&headers), None);
+
+        headers.insert("icy-metaint", HeaderValue::from_static(" 16000 "));
+        assert_eq!(metaint(&headers), Some(16000));
+
+        headers.insert("icy-metaint", HeaderValue::from_static("0"));
+        assert_eq!(metaint(&headers), None);
+
+        headers.insert("icy-metaint", HeaderValue::from_static("lots"));
+        assert_eq!(metaint(&headers), None);
+    }
+}
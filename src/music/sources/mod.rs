@@ -0,0 +1,228 @@
+pub mod file;
+pub mod http;
+pub mod icy;
+pub mod ytdlp;
+
+use std::path::PathBuf;
+
+use async_trait::async_trait;
+use songbird::input::{AuxMetadata, Input};
+use tokio::sync::mpsc;
+
+use crate::music::track::Artwork;
+
+use self::{
+    file::LocalFile,
+    http::HttpAudio,
+    ytdlp::{YtDlpPlaylist, YtDlpSearch},
+};
+
+/// A track ready to be enqueued.
+pub struct ResolvedTrack {
+    pub input: Input,
+    pub metadata: AuxMetadata,
+    /// What to give the resolver to get this track again.
+    pub query: String,
+    pub artwork: Option<Artwork>,
+    /// Whether the source is a radio stream announcing its titles in ICY metadata.
+    pub icy: bool,
+}
+
+/// Turns a `/play` query into playable tracks.
+#[async_trait]
+pub trait SourceResolver: Send + Sync {
+    /// Whether this resolver handles `query`.
+    async fn matches(&self, query: &str) -> bool;
+
+    /// Resolves `query`, sending each track to `tracks` as soon as it is ready.
+    ///
+    /// Resolution should stop early once `tracks` is closed.
+    async fn resolve(&self, query: &str, tracks: mpsc::Sender<ResolvedTrack>)
+    -> anyhow::Result<()>;
+
+    /// Recreates the input of a track from the [`ResolvedTrack::query`] it was resolved with,
+    /// without fetching its metadata again.
+    async fn reload(&self, query: &str) -> anyhow::Result<Input>;
+}
+
+/// The resolvers available to `/play`, tried in order.
+pub struct Sources {
+    resolvers: Vec<Box<dyn SourceResolver>>,
+}
+
+impl Sources {
+    pub fn new(resolvers: Vec<Box<dyn SourceResolver>>) -> Self {
+        Self { resolvers }
+    }
+
+    /// The default resolvers, with local files only playable if a `media_library` is configured.
+    pub fn with_defaults(client: reqwest::Client, media_library: Option<PathBuf>) -> Self {
+        let mut resolvers: Vec<Box<dyn SourceResolver>> = Vec::new();
+        if let Some(root) = media_library {
+            resolvers.push(Box::new(LocalFile::new(root)));
+        }
+        resolvers.push(Box::new(HttpAudio::new(client.clone())));
+        resolvers.push(Box::new(YtDlpPlaylist::new(client.clone())));
+        resolvers.push(Box::new(YtDlpSearch::new(client)));
+
+        Self::new(resolvers)
+    }
+
+    pub async fn resolver(&self, query: &str) -> Option<&dyn SourceResolver> {
+        for resolver in &self.resolvers {
+            if resolver.matches(query).await {
+                return Some(resolver.as_ref());
+            }
+        }
+
+        None
+    }
+
+    /// Recreates the input of a previously resolved track, e.g. to queue it again.
+    pub async fn reload(&self, query: &str) -> anyhow::Result<Input> {
+        match self.resolver(query).await {
+            Some(resolver) => resolver.reload(query).await,
+            None => anyhow::bail!("no source for {}", query),
+        }
+    }
+}
+
+pub fn is_http_url(query: &str) -> bool {
+    query.starts_with("http://") || query.starts_with("https://")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+
+    type Reloads = Arc<Mutex<Vec<(&'static str, String)>>>;
+
+    /// Stands in for a real resolver, logging the queries it is asked to reload.
+    struct Fake {
+        name: &'static str,
+        matches: fn(&str) -> bool,
+        reloads: Reloads,
+    }
+
+    #[async_trait]
+    impl SourceResolver for Fake {
+        async fn matches(&self, query: &str) -> bool {
+            (self.matches)(query)
+        }
+
+        async fn resolve(
+            &self,
+            _query: &str,
+            _tracks: mpsc::Sender<ResolvedTrack>,
+        ) -> anyhow::Result<()> {
+            Ok(())
+        }
+
+        async fn reload(&self, query: &str) -> anyhow::Result<Input> {
+            self.reloads
+                .lock()
+                .unwrap()
+                .push((self.name, query.to_owned()));
+            anyhow::bail!("{} has nothing to play", self.name)
+        }
+    }
+
+    /// Fakes of the default resolvers, in the same order. The HTTP one takes `.mp3` URLs for
+    /// audio, as probing would.
+    fn sources(library: bool, reloads: &Reloads) -> Sources {
+        let fake = |name, matches| -> Box<dyn SourceResolver> {
+            Box::new(Fake {
+                name,
+                matches,
+                reloads: reloads.clone(),
+            })
+        };
+
+        let mut resolvers = Vec::new();
+        if library {
+            resolvers.push(fake("file", |query| query.starts_with("file:")));
+        }
+        resolvers.push(fake("http", |query| {
+            is_http_url(query) && query.ends_with(".mp3")
+        }));
+        resolvers.push(fake("playlist", is_http_url));
+        resolvers.push(fake("search", |_| true));
+
+        Sources::new(resolvers)
+    }
+
+    /// The name of the resolver `sources` picks for `query`.
+    async fn picked(sources: &Sources, reloads: &Reloads, query: &str) -> Option<&'static str> {
+        let resolver = sources.resolver(query).await?;
+        _ = resolver.reload(query).await;
+        reloads.lock().unwrap().pop().map(|(name, _)| name)
+    }
+
+    #[tokio::test]
+    async fn picks_the_first_matching_resolver() {
+        let reloads = Reloads::default();
+        let sources = sources(true, &reloads);
+
+        for (query, expected) in [
+            ("file:albums/song.flac", "file"),
+            ("https://radio.example/live.mp3", "http"),
+            ("http://radio.example/live.mp3", "http"),
+            ("https://www.youtube.com/watch?v=dQw4w9WgXcQ", "playlist"),
+            ("https://soundcloud.com/artist/sets/album", "playlist"),
+            ("never gonna give you up", "search"),
+            ("radio.example/live.mp3", "search"),
+        ] {
+            assert_eq!(
+                picked(&sources, &reloads, query).await,
+                Some(expected),
+                "{}",
+                query
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn local_files_fall_through_without_a_library() {
+        let reloads = Reloads::default();
+        let sources = sources(false, &reloads);
+
+        assert_eq!(
+            picked(&sources, &reloads, "file:song.mp3").await,
+            Some("search")
+        );
+    }
+
+    #[tokio::test]
+    async fn reloads_through_the_matching_resolver() {
+        let reloads = Reloads::default();
+        let sources = sources(true, &reloads);
+
+        for query in [
+            "file:song.mp3",
+            "https://www.youtube.com/watch?v=dQw4w9WgXcQ",
+        ] {
+            assert!(sources.reload(query).await.is_err());
+        }
+
+        assert_eq!(
+            *reloads.lock().unwrap(),
+            [
+                ("file", "file:song.mp3".to_owned()),
+                (
+                    "playlist",
+                    "https://www.youtube.com/watch?v=dQw4w9WgXcQ".to_owned()
+                ),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn fails_to_reload_without_a_matching_resolver() {
+        let sources = Sources::new(Vec::new());
+
+        assert!(sources.resolver("song").await.is_none());
+        assert!(sources.reload("song").await.is_err());
+    }
+}
@@ -0,0 +1,348 @@
+use std::{process::Stdio, time::Duration};
+
+use anyhow::Context as _;
+use async_trait::async_trait;
+use serde::Deserialize;
+use songbird::input::{AuxMetadata, Compose, Input, YoutubeDl};
+use tokio::{
+    io::{AsyncBufReadExt, BufReader, Lines},
+    process::{Child, ChildStdout, Command},
+    sync::mpsc,
+    time::{Instant, timeout_at},
+};
+
+use crate::music::sources::{ResolvedTrack, SourceResolver, is_http_url};
+
+/// How long yt-dlp may take to list a whole playlist.
+const PLAYLIST_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// A playlist entry, as printed by `yt-dlp -j --flat-playlist`.
+#[derive(Debug, Deserialize)]
+pub struct Entry {
+    /// `url` for entries of a flat playlist, `video` for a fully extracted single video.
+    #[serde(rename = "_type")]
+    pub kind: Option<String>,
+    pub url: Option<String>,
+    pub webpage_url: Option<String>,
+    pub title: Option<String>,
+    pub duration: Option<f64>,
+    pub uploader: Option<String>,
+    pub channel: Option<String>,
+    #[serde(default)]
+    pub thumbnails: Vec<Thumbnail>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Thumbnail {
+    pub url: String,
+}
+
+impl Entry {
+    /// The URL yt-dlp should be given to play this entry.
+    ///
+    /// Flat playlist entries point at their page in `url`, but a fully extracted video has
+    /// its media there, which expires, so its page is used instead.
+    pub fn url(&self) -> Option<&str> {
+        match self.kind.as_deref() {
+            Some("url" | "url_transparent") => self.url.as_deref().or(self.webpage_url.as_deref()),
+            _ => self.webpage_url.as_deref().or(self.url.as_deref()),
+        }
+    }
+
+    pub fn metadata(&self) -> AuxMetadata {
+        AuxMetadata {
+            title: self.title.clone(),
+            artist: self.uploader.clone(),
+            channel: self.channel.clone().or_else(|| self.uploader.clone()),
+            duration: self
+                .duration
+                .filter(|duration| duration.is_finite() && *duration > 0.0)
+                .map(Duration::from_secs_f64),
+            source_url: self.webpage_url.clone().or_else(|| self.url.clone()),
+            // yt-dlp lists thumbnails from worst to best.
+            thumbnail: self
+                .thumbnails
+                .last()
+                .map(|thumbnail| thumbnail.url.clone()),
+            ..Default::default()
+        }
+    }
+
+    /// Turns this entry into a track played through yt-dlp.
+    pub fn track(&self, client: reqwest::Client) -> ResolvedTrack {
+        let url = self.url().unwrap_or_default().to_owned();
+        ResolvedTrack {
+            input: YoutubeDl::new(client, url.clone()).into(),
+            metadata: self.metadata(),
+            query: url,
+            artwork: None,
+            icy: false,
+        }
+    }
+}
+
+/// Returns the first `count` YouTube results for `query`.
+pub async fn search(query: &str, count: usize) -> anyhow::Result<Vec<Entry>> {
+    let mut playlist = Playlist::spawn(&format!("ytsearch{}:{}", count, query))?;
+
+    let mut entries = Vec::with_capacity(count);
+    while let Some(entry) = playlist.next().await? {
+        entries.push(entry);
+    }
+
+    Ok(entries)
+}
+
+/// The entries of a playlist, read from yt-dlp as it resolves them.
+///
+/// Dropping the playlist kills yt-dlp if it is still running.
+pub struct Playlist {
+    child: Child,
+    lines: Lines<BufReader<ChildStdout>>,
+    deadline: Instant,
+}
+
+impl Playlist {
+    pub fn spawn(url: &str) -> anyhow::Result<Self> {
+        let mut child = Command::new("yt-dlp")
+            // URLs of a video within a playlist play the video, as in a browser.
+            .args(["-j", "--flat-playlist", "--no-playlist", url])
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .kill_on_drop(true)
+            .spawn()
+            .context("failed to spawn yt-dlp")?;
+
+        let stdout = child.stdout.take().context("yt-dlp has no stdout")?;
+
+        Ok(Self {
+            child,
+            lines: BufReader::new(stdout).lines(),
+            deadline: Instant::now() + PLAYLIST_TIMEOUT,
+        })
+    }
+
+    /// Returns the next playable entry, or `None` once the playlist is exhausted.
+    pub async fn next(&mut self) -> anyhow::Result<Option<Entry>> {
+        loop {
+            let line = timeout_at(self.deadline, self.lines.next_line())
+                .await
+                .context("timed out while reading playlist")??;
+
+            let Some(line) = line else {
+                let status = self.child.wait().await?;
+                if !status.success() {
+                    tracing::warn!("yt-dlp exited with {}", status);
+                }
+
+                return Ok(None);
+            };
+
+            match serde_json::from_str::<Entry>(&line) {
+                Ok(entry) if entry.url().is_some() => return Ok(Some(entry)),
+                Ok(_) => tracing::debug!("skipping playlist entry without url"),
+                Err(error) => tracing::warn!(?error, "failed to parse playlist entry"),
+            }
+        }
+    }
+}
+
+/// Plays the first yt-dlp search result for queries that are not URLs.
+pub struct YtDlpSearch {
+    client: reqwest::Client,
+}
+
+impl YtDlpSearch {
+    pub fn new(client: reqwest::Client) -> Self {
+        Self { client }
+    }
+}
+
+#[async_trait]
+impl SourceResolver for YtDlpSearch {
+    async fn matches(&self, _query: &str) -> bool {
+        true
+    }
+
+    async fn resolve(
+        &self,
+        query: &str,
+        tracks: mpsc::Sender<ResolvedTrack>,
+    ) -> anyhow::Result<()> {
+        let mut src = YoutubeDl::new_search(self.client.clone(), query.to_owned());
+        let metadata = src.aux_metadata().await?;
+
+        // Queue the result by URL, so replaying it does not depend on search ranking.
+        let query = metadata
+            .source_url
+            .clone()
+            .unwrap_or_else(|| query.to_owned());
+        let track = ResolvedTrack {
+            input: src.into(),
+            metadata,
+            query,
+            artwork: None,
+            icy: false,
+        };
+        _ = tracks.send(track).await;
+
+        Ok(())
+    }
+
+    async fn reload(&self, query: &str) -> anyhow::Result<Input> {
+        // Search results are stored by URL, unless the search did not report one.
+        let src = match is_http_url(query) {
+            true => YoutubeDl::new(self.client.clone(), query.to_owned()),
+            false => YoutubeDl::new_search(self.client.clone(), query.to_owned()),
+        };
+        Ok(src.into())
+    }
+}
+
+/// Plays URLs supported by yt-dlp, queueing the entries of playlists as yt-dlp lists them.
+///
+/// Whether a URL is a playlist is left to yt-dlp, so playlists of every extractor work. A
+/// single video comes out as a playlist of one.
+pub struct YtDlpPlaylist {
+    client: reqwest::Client,
+}
+
+impl YtDlpPlaylist {
+    pub fn new(client: reqwest::Client) -> Self {
+        Self { client }
+    }
+}
+
+#[async_trait]
+impl SourceResolver for YtDlpPlaylist {
+    async fn matches(&self, query: &str) -> bool {
+        is_http_url(query)
+    }
+
+    async fn resolve(
+        &self,
+        query: &str,
+        tracks: mpsc::Sender<ResolvedTrack>,
+    ) -> anyhow::Result<()> {
+        let mut playlist = Playlist::spawn(query)?;
+
+        while let Some(entry) = playlist.next().await? {
+            if tracks.send(entry.track(self.client.clone())).await.is_err() {
+                break;
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn reload(&self, query: &str) -> anyhow::Result<Input> {
+        // Tracks of a playlist are stored by the URL of their entry.
+        Ok(YoutubeDl::new(self.client.clone(), query.to_owned()).into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(json: &str) -> Entry {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn parses_youtube_entry() {
+        let entry = entry(
+            r#"{
+                "_type": "url",
+                "ie_key": "Youtube",
+                "id": "dQw4w9WgXcQ",
+                "url": "https://www.youtube.com/watch?v=dQw4w9WgXcQ",
+                "title": "Never Gonna Give You Up",
+                "duration": 212.0,
+                "channel": "Rick Astley",
+                "uploader": null,
+                "thumbnails": [
+                    {"url": "https://i.ytimg.com/vi/dQw4w9WgXcQ/hqdefault.jpg", "height": 94},
+                    {"url": "https://i.ytimg.com/vi/dQw4w9WgXcQ/maxresdefault.jpg"}
+                ]
+            }"#,
+        );
+        let metadata = entry.metadata();
+
+        assert_eq!(
+            entry.url(),
+            Some("https://www.youtube.com/watch?v=dQw4w9WgXcQ")
+        );
+        assert_eq!(metadata.title.as_deref(), Some("Never Gonna Give You Up"));
+        assert_eq!(metadata.duration, Some(Duration::from_secs(212)));
+        assert_eq!(metadata.channel.as_deref(), Some("Rick Astley"));
+        assert_eq!(metadata.artist, None);
+        assert_eq!(
+            metadata.source_url.as_deref(),
+            Some("https://www.youtube.com/watch?v=dQw4w9WgXcQ")
+        );
+        assert_eq!(
+            metadata.thumbnail.as_deref(),
+            Some("https://i.ytimg.com/vi/dQw4w9WgXcQ/maxresdefault.jpg")
+        );
+    }
+
+    #[test]
+    fn parses_entry_with_only_webpage_url() {
+        let entry = entry(
+            r#"{
+                "_type": "url",
+                "ie_key": "Soundcloud",
+                "webpage_url": "https://soundcloud.com/artist/song",
+                "title": "Song",
+                "uploader": "Artist"
+            }"#,
+        );
+        let metadata = entry.metadata();
+
+        assert_eq!(entry.url(), Some("https://soundcloud.com/artist/song"));
+        assert_eq!(metadata.title.as_deref(), Some("Song"));
+        assert_eq!(metadata.artist.as_deref(), Some("Artist"));
+        assert_eq!(metadata.channel.as_deref(), Some("Artist"));
+        assert_eq!(metadata.duration, None);
+        assert_eq!(metadata.thumbnail, None);
+        assert_eq!(
+            metadata.source_url.as_deref(),
+            Some("https://soundcloud.com/artist/song")
+        );
+    }
+
+    #[test]
+    fn plays_full_videos_by_their_page() {
+        let entry = entry(
+            r#"{
+                "_type": "video",
+                "url": "https://rr1---sn-example.googlevideo.com/videoplayback?expire=1",
+                "webpage_url": "https://www.youtube.com/watch?v=dQw4w9WgXcQ",
+                "duration": null
+            }"#,
+        );
+
+        assert_eq!(
+            entry.url(),
+            Some("https://www.youtube.com/watch?v=dQw4w9WgXcQ")
+        );
+        assert_eq!(entry.metadata().duration, None);
+        assert_eq!(entry.metadata().title, None);
+    }
+
+    #[test]
+    fn ignores_invalid_durations() {
+        assert_eq!(entry(r#"{"duration": 0}"#).metadata().duration, None);
+        assert_eq!(entry(r#"{"duration": -5.5}"#).metadata().duration, None);
+        assert_eq!(
+            entry(r#"{"duration": 1.5}"#).metadata().duration,
+            Some(Duration::from_millis(1500))
+        );
+    }
+
+    #[test]
+    fn entry_without_url_cannot_play() {
+        assert_eq!(entry(r#"{"title": "Deleted video"}"#).url(), None);
+    }
+}
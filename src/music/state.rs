@@ -0,0 +1,90 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::RwLock,
+};
+
+use tokio::task::AbortHandle;
+use twilight_interactions::command::{CommandOption, CreateOption};
+use twilight_model::id::{
+    Id,
+    marker::{ChannelMarker, GuildMarker, UserMarker},
+};
+use uuid::Uuid;
+
+/// What is repeated once a track finishes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, CommandOption, CreateOption)]
+pub enum LoopMode {
+    #[default]
+    #[option(name = "off", value = "off")]
+    Off,
+    /// The current track plays again until the mode changes or it is skipped.
+    #[option(name = "track", value = "track")]
+    Track,
+    /// Finished tracks are queued again at the end of the queue.
+    #[option(name = "queue", value = "queue")]
+    Queue,
+}
+
+impl LoopMode {
+    pub fn label(self) -> &'static str {
+        match self {
+            LoopMode::Off => "Off",
+            LoopMode::Track => "Track",
+            LoopMode::Queue => "Queue",
+        }
+    }
+
+    /// The mode the loop button switches to.
+    pub fn next(self) -> Self {
+        match self {
+            LoopMode::Off => LoopMode::Track,
+            LoopMode::Track => LoopMode::Queue,
+            LoopMode::Queue => LoopMode::Off,
+        }
+    }
+}
+
+/// Playback settings of a guild that outlive single tracks.
+#[derive(Debug, Clone, Default)]
+pub struct GuildState {
+    pub loop_mode: LoopMode,
+    pub skip_votes: Option<SkipVotes>,
+    /// Where the guild last used a command, for messages not answering one.
+    pub text_channel: Option<Id<ChannelMarker>>,
+    /// Timer that makes the bot leave if it is still idle when it runs out.
+    pub idle_timer: Option<AbortHandle>,
+    /// Whether the bot had no listeners at the last voice state update.
+    pub alone: bool,
+    /// Whether playback was paused because every listener left.
+    pub paused_alone: bool,
+    /// Whether the bot is trying to get a dropped voice connection back.
+    pub recovering: bool,
+}
+
+/// Users who voted to skip a track.
+#[derive(Debug, Clone)]
+pub struct SkipVotes {
+    pub track: Uuid,
+    pub voters: HashSet<Id<UserMarker>>,
+}
+
+/// Per-guild [`GuildState`], defaulted for guilds that never changed it.
+#[derive(Default)]
+pub struct GuildStates {
+    guilds: RwLock<HashMap<Id<GuildMarker>, GuildState>>,
+}
+
+impl GuildStates {
+    pub fn get(&self, guild_id: Id<GuildMarker>) -> GuildState {
+        self.guilds
+            .read()
+            .unwrap()
+            .get(&guild_id)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    pub fn update<T>(&self, guild_id: Id<GuildMarker>, f: impl FnOnce(&mut GuildState) -> T) -> T {
+        f(self.guilds.write().unwrap().entry(guild_id).or_default())
+    }
+}
@@ -0,0 +1,163 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{Arc, Mutex, RwLock},
+    time::{Duration, Instant},
+};
+
+use twilight_model::id::{
+    Id,
+    marker::{GuildMarker, UserMarker},
+};
+
+use crate::{
+    music::{embeds, sources::ytdlp},
+    utils::truncate,
+};
+
+/// How many recent queries are remembered per guild.
+const HISTORY_SIZE: usize = 25;
+const SEARCH_RESULTS: usize = 5;
+/// How long a yt-dlp search result stays cached.
+const SEARCH_TTL: Duration = Duration::from_secs(10 * 60);
+const SEARCH_CACHE_SIZE: usize = 256;
+/// How long a user must stop typing before a search is started.
+const DEBOUNCE: Duration = Duration::from_millis(400);
+/// Autocomplete must be answered within 3 seconds, so slower searches only fill the cache.
+const SEARCH_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// A choice offered for the `/play` query.
+#[derive(Debug, Clone)]
+pub struct Suggestion {
+    pub name: String,
+    pub query: String,
+}
+
+impl Suggestion {
+    /// Choice names are limited to 100 characters.
+    pub fn name(&self) -> String {
+        truncate(&self.name, 100)
+    }
+}
+
+struct CachedSearch {
+    searched_at: Instant,
+    results: Vec<Suggestion>,
+}
+
+/// Suggestions for the `/play` query, from the guild's play history and yt-dlp searches.
+#[derive(Default)]
+pub struct Suggestions {
+    history: RwLock<HashMap<Id<GuildMarker>, VecDeque<Suggestion>>>,
+    searches: Arc<Mutex<HashMap<String, CachedSearch>>>,
+    /// When each user last asked for a search, to only run the one for their last keystroke.
+    latest: Mutex<HashMap<Id<UserMarker>, Instant>>,
+}
+
+impl Suggestions {
+    /// Remembers that `query` was played in `guild_id`, moving it to the front if it already was.
+    pub fn record(&self, guild_id: Id<GuildMarker>, name: &str, query: &str) {
+        let mut history = self.history.write().unwrap();
+        let history = history.entry(guild_id).or_default();
+
+        history.retain(|suggestion| suggestion.query != query);
+        history.push_front(Suggestion {
+            name: name.to_owned(),
+            query: query.to_owned(),
+        });
+        history.truncate(HISTORY_SIZE);
+    }
+
+    /// Recently played queries of `guild_id` whose name or query contains `input`.
+    pub fn history(&self, guild_id: Id<GuildMarker>, input: &str) -> Vec<Suggestion> {
+        let input = input.to_lowercase();
+
+        self.history
+            .read()
+            .unwrap()
+            .get(&guild_id)
+            .map(|history| {
+                history
+                    .iter()
+                    .filter(|suggestion| {
+                        suggestion.name.to_lowercase().contains(&input)
+                            || suggestion.query.to_lowercase().contains(&input)
+                    })
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// yt-dlp search results for `input`, typed by `user_id`.
+    ///
+    /// Results are cached per query. A search is only started once the user stopped typing,
+    /// and yields nothing if it does not finish in time for the autocomplete response.
+    pub async fn search(&self, user_id: Id<UserMarker>, input: &str) -> Vec<Suggestion> {
+        let key = input.trim().to_lowercase();
+        if let Some(results) = self.cached(&key) {
+            return results;
+        }
+
+        let requested_at = Instant::now();
+        self.latest.lock().unwrap().insert(user_id, requested_at);
+        tokio::time::sleep(DEBOUNCE).await;
+        if self.latest.lock().unwrap().get(&user_id) != Some(&requested_at) {
+            return Vec::new();
+        }
+
+        let searches = self.searches.clone();
+        let search = tokio::spawn(async move {
+            let results: Vec<_> = ytdlp::search(&key, SEARCH_RESULTS)
+                .await?
+                .iter()
+                .filter_map(|entry| {
+                    let metadata = entry.metadata();
+                    Some(Suggestion {
+                        name: format!(
+                            "{} ({})",
+                            embeds::title(&metadata),
+                            embeds::duration(&metadata)
+                        ),
+                        query: entry.url()?.to_owned(),
+                    })
+                })
+                .collect();
+
+            let mut searches = searches.lock().unwrap();
+            searches.retain(|_, search| search.searched_at.elapsed() < SEARCH_TTL);
+            if searches.len() < SEARCH_CACHE_SIZE {
+                searches.insert(
+                    key,
+                    CachedSearch {
+                        searched_at: Instant::now(),
+                        results: results.clone(),
+                    },
+                );
+            }
+
+            anyhow::Ok(results)
+        });
+
+        match tokio::time::timeout(SEARCH_TIMEOUT, search).await {
+            Ok(Ok(Ok(results))) => results,
+            Ok(Ok(Err(error))) => {
+                tracing::debug!(?error, "autocomplete search for {} failed", input);
+                Vec::new()
+            }
+            Ok(Err(error)) => {
+                tracing::warn!(?error, "autocomplete search task failed");
+                Vec::new()
+            }
+            Err(_) => Vec::new(),
+        }
+    }
+
+    fn cached(&self, key: &str) -> Option<Vec<Suggestion>> {
+        self.searches
+            .lock()
+            .unwrap()
+            .get(key)
+            .filter(|search| search.searched_at.elapsed() < SEARCH_TTL)
+            .map(|search| search.results.clone())
+    }
+}
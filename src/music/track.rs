@@ -0,0 +1,93 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+    time::SystemTime,
+};
+
+use songbird::input::AuxMetadata;
+use twilight_model::id::{
+    Id,
+    marker::{ChannelMarker, GuildMarker, UserMarker},
+};
+use uuid::Uuid;
+
+/// Everything known about a track at the time it was enqueued.
+pub struct TrackData {
+    pub requester: Id<UserMarker>,
+    pub channel_id: Id<ChannelMarker>,
+    pub metadata: AuxMetadata,
+    pub queued_at: SystemTime,
+    pub query: String,
+    pub artwork: Option<Artwork>,
+    /// Whether the track is a radio stream announcing its titles in ICY metadata.
+    pub icy: bool,
+    /// What a radio stream currently plays, as announced in its ICY metadata.
+    pub stream_title: RwLock<Option<String>>,
+    /// Volume in percent set for this track only, overriding the guild volume.
+    pub volume: RwLock<Option<u16>>,
+}
+
+/// Cover art embedded in a local file, uploaded along with the "Now playing" message.
+#[derive(Clone)]
+pub struct Artwork {
+    pub media_type: String,
+    pub data: Vec<u8>,
+}
+
+impl Artwork {
+    /// The attachment name embeds refer to the artwork by.
+    pub fn file_name(&self) -> String {
+        let extension = match self.media_type.as_str() {
+            "image/png" => "png",
+            "image/gif" => "gif",
+            "image/webp" => "webp",
+            _ => "jpg",
+        };
+
+        format!("cover.{}", extension)
+    }
+}
+
+/// Per-guild registry of [`TrackData`], keyed by the songbird track UUID.
+#[derive(Default)]
+pub struct TrackRegistry {
+    guilds: RwLock<HashMap<Id<GuildMarker>, HashMap<Uuid, Arc<TrackData>>>>,
+}
+
+impl TrackRegistry {
+    pub fn insert(&self, guild_id: Id<GuildMarker>, uuid: Uuid, data: TrackData) -> Arc<TrackData> {
+        let data = Arc::new(data);
+        self.guilds
+            .write()
+            .unwrap()
+            .entry(guild_id)
+            .or_default()
+            .insert(uuid, data.clone());
+
+        data
+    }
+
+    pub fn get(&self, guild_id: Id<GuildMarker>, uuid: Uuid) -> Option<Arc<TrackData>> {
+        self.guilds
+            .read()
+            .unwrap()
+            .get(&guild_id)
+            .and_then(|tracks| tracks.get(&uuid))
+            .cloned()
+    }
+
+    pub fn remove(&self, guild_id: Id<GuildMarker>, uuid: Uuid) -> Option<Arc<TrackData>> {
+        let mut guilds = self.guilds.write().unwrap();
+        let tracks = guilds.get_mut(&guild_id)?;
+        let data = tracks.remove(&uuid);
+        if tracks.is_empty() {
+            guilds.remove(&guild_id);
+        }
+
+        data
+    }
+
+    pub fn clear(&self, guild_id: Id<GuildMarker>) {
+        self.guilds.write().unwrap().remove(&guild_id);
+    }
+}
@@ -0,0 +1,87 @@
+use anyhow::bail;
+use twilight_model::{
+    application::interaction::Interaction,
+    id::{
+        Id,
+        marker::{ChannelMarker, GuildMarker, UserMarker},
+    },
+};
+
+use crate::{Context, error::CommandError, music::controls};
+
+/// Commands that control playback, which only listeners in the bot's voice channel may use.
+const CONTROLS: &[&str] = &[
+    "pause", "resume", "skip", "stop", "seek", "replay", "volume", "loop", "shuffle", "remove",
+    "move", "clear", "join", "leave",
+];
+
+/// Fails if `command` controls playback and the user of `interaction` is not in the voice
+/// channel of the bot.
+///
+/// A bot that is not playing has nothing for others to lose, so anyone may use it.
+pub async fn check(ctx: &Context, interaction: &Interaction, command: &str) -> anyhow::Result<()> {
+    if !CONTROLS.contains(&command) {
+        return Ok(());
+    }
+    let Some(guild_id) = interaction.guild_id else {
+        return Ok(());
+    };
+    let Some(channel_id) = bot_channel(ctx, guild_id).await else {
+        return Ok(());
+    };
+    if controls::current(ctx, guild_id).await.is_none() {
+        return Ok(());
+    }
+
+    match user_channel(ctx, guild_id, interaction.author().unwrap().id) {
+        Some(user_channel) if user_channel == channel_id => Ok(()),
+        _ => bail!(CommandError::NotInBotChannel(channel_id)),
+    }
+}
+
+/// The voice channel `user_id` is in, as far as the cache knows.
+pub fn user_channel(
+    ctx: &Context,
+    guild_id: Id<GuildMarker>,
+    user_id: Id<UserMarker>,
+) -> Option<Id<ChannelMarker>> {
+    ctx.cache
+        .voice_state(user_id, guild_id)
+        .map(|state| state.channel_id())
+}
+
+/// The voice channel the bot is connected to in `guild_id`.
+pub async fn bot_channel(ctx: &Context, guild_id: Id<GuildMarker>) -> Option<Id<ChannelMarker>> {
+    let call_lock = ctx.songbird.get(guild_id)?;
+    let channel = call_lock.lock().await.current_channel()?;
+
+    Some(Id::from(channel.0))
+}
+
+/// Users in the bot's voice channel, leaving out bots.
+///
+/// Users the cache has not seen yet are counted, as they most likely are not bots.
+pub async fn listeners(ctx: &Context, guild_id: Id<GuildMarker>) -> Vec<Id<UserMarker>> {
+    let Some(channel_id) = bot_channel(ctx, guild_id).await else {
+        return Vec::new();
+    };
+    let Some(states) = ctx.cache.voice_channel_states(channel_id) else {
+        return Vec::new();
+    };
+
+    states
+        .map(|state| state.user_id())
+        .filter(|user_id| !ctx.cache.user(*user_id).is_some_and(|user| user.bot))
+        .collect()
+}
+
+/// Posts `content` in the channel the guild last used a command in.
+pub async fn notify(ctx: &Context, guild_id: Id<GuildMarker>, content: &str) {
+    let Some(channel_id) = ctx.guilds.get(guild_id).text_channel else {
+        return;
+    };
+
+    if let Err(error) = ctx.client.create_message(channel_id).content(content).await {
+        tracing::warn!(?error, "failed to post notice");
+    }
+}
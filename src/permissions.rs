@@ -0,0 +1,109 @@
+use anyhow::bail;
+use twilight_model::{
+    application::interaction::Interaction,
+    guild::Permissions,
+    id::{
+        Id,
+        marker::{GuildMarker, RoleMarker, UserMarker},
+    },
+};
+
+use crate::{
+    Context,
+    error::CommandError,
+    music::{controls, voice},
+};
+
+/// Commands that change playback for everyone, limited to DJs once the guild sets a DJ role.
+const DJ_COMMANDS: &[&str] = &[
+    "stop", "clear", "shuffle", "move", "remove", "seek", "volume", "loop", "join", "leave",
+];
+
+/// Fails with [`CommandError::PermissionDenied`] if the user of `interaction` may not use
+/// `command` in its guild.
+///
+/// A command with allowed roles is limited to them, and DJ commands to DJs if there is a
+/// DJ role. DJs and members who can manage the guild may use every command.
+pub async fn check(ctx: &Context, interaction: &Interaction, command: &str) -> anyhow::Result<()> {
+    let (Some(guild_id), Some(member)) = (interaction.guild_id, interaction.member.as_ref()) else {
+        return Ok(());
+    };
+    if member
+        .permissions
+        .is_some_and(|permissions| permissions.contains(Permissions::MANAGE_GUILD))
+    {
+        return Ok(());
+    }
+
+    let settings = ctx.settings.get(guild_id);
+    let allowed = settings.allowed_roles.get(command);
+    let dj_only = settings.dj_role.is_some() && DJ_COMMANDS.contains(&command);
+    if allowed.is_none() && !dj_only {
+        return Ok(());
+    }
+    if allowed.is_some_and(|roles| member.roles.iter().any(|role| roles.contains(role))) {
+        return Ok(());
+    }
+
+    let user_id = interaction.author().unwrap().id;
+    if is_dj(ctx, guild_id, user_id, &member.roles).await {
+        return Ok(());
+    }
+    if command == "skip" && settings.requester_skip && is_requester(ctx, guild_id, user_id).await {
+        return Ok(());
+    }
+
+    match allowed {
+        Some(_) => bail!(CommandError::PermissionDenied(format!(
+            "Your roles do not allow using /{} here.",
+            command
+        ))),
+        None => bail!(CommandError::PermissionDenied(format!(
+            "Only DJs can use /{}.",
+            command
+        ))),
+    }
+}
+
+/// Whether the user counts as a DJ, by role or, if the guild allows it, by being the only
+/// listener left with the bot.
+pub async fn is_dj(
+    ctx: &Context,
+    guild_id: Id<GuildMarker>,
+    user_id: Id<UserMarker>,
+    roles: &[Id<RoleMarker>],
+) -> bool {
+    let settings = ctx.settings.get(guild_id);
+    if settings.is_dj(roles) {
+        return true;
+    }
+
+    settings.alone_is_dj && voice::listeners(ctx, guild_id).await == [user_id]
+}
+
+/// Whether the user requested the current track.
+pub async fn is_requester(
+    ctx: &Context,
+    guild_id: Id<GuildMarker>,
+    user_id: Id<UserMarker>,
+) -> bool {
+    let Some(handle) = controls::current(ctx, guild_id).await else {
+        return false;
+    };
+
+    ctx.trackdata
+        .get(guild_id, handle.uuid())
+        .is_some_and(|data| data.requester == user_id)
+}
+
+/// The command a component belongs to, as far as permissions go.
+///
+/// Now playing buttons stand for the command they do the same as.
+pub fn component_command(custom_id: &str) -> &str {
+    let mut parts = custom_id.split(':');
+    match (parts.next(), parts.next()) {
+        (Some("nowplaying"), Some(action)) => action,
+        (Some(command), _) => command,
+        (None, _) => custom_id,
+    }
+}
@@ -1,15 +1,9 @@
 use std::mem;
 
-use anyhow::bail;
 use twilight_gateway::Event;
-use twilight_model::application::interaction::{
-    application_command::CommandData, Interaction, InteractionData,
-};
+use twilight_model::application::interaction::{Interaction, InteractionData, InteractionType};
 
-use crate::{
-    music::{PauseCommand, PlayCommand, ResumeCommand, SkipCommand, StopCommand},
-    Context, PingCommand,
-};
+use crate::{Context, command::report_error, music::voice, permissions};
 
 pub async fn process_interactions(event: Event, ctx: Context) {
     let mut interaction = match event {
@@ -17,31 +11,54 @@ pub async fn process_interactions(event: Event, ctx: Context) {
         _ => return,
     };
 
-    let data = match mem::take(&mut interaction.data) {
-        Some(InteractionData::ApplicationCommand(data)) => *data,
-        _ => {
-            tracing::warn!("ignoring non-command interaction");
-            return;
-        }
-    };
+    if let (Some(guild_id), Some(channel)) = (interaction.guild_id, &interaction.channel) {
+        ctx.guilds
+            .update(guild_id, |state| state.text_channel = Some(channel.id));
+    }
 
-    if let Err(error) = handle_command(interaction, data, &ctx).await {
-        tracing::error!(?error, "error while handling command");
+    match mem::take(&mut interaction.data) {
+        Some(InteractionData::ApplicationCommand(data))
+            if interaction.kind == InteractionType::ApplicationCommandAutocomplete =>
+        {
+            if let Err(error) = ctx.commands.autocomplete(interaction, *data, &ctx).await {
+                tracing::error!(?error, "error while handling autocomplete");
+            }
+        }
+        Some(InteractionData::ApplicationCommand(data)) => {
+            let result = match allowed(&ctx, &interaction, &data.name).await {
+                Ok(()) => ctx.commands.handle(interaction.clone(), *data, &ctx).await,
+                Err(error) => Err(error),
+            };
+            if let Err(error) = result {
+                report(&ctx, &interaction, error).await;
+            }
+        }
+        Some(InteractionData::MessageComponent(data)) => {
+            let command = permissions::component_command(&data.custom_id);
+            let result = match allowed(&ctx, &interaction, command).await {
+                Ok(()) => {
+                    ctx.commands
+                        .component(interaction.clone(), *data, &ctx)
+                        .await
+                }
+                Err(error) => Err(error),
+            };
+            if let Err(error) = result {
+                report(&ctx, &interaction, error).await;
+            }
+        }
+        _ => tracing::warn!("ignoring unsupported interaction"),
     }
 }
 
-async fn handle_command(
-    interaction: Interaction,
-    data: CommandData,
-    ctx: &Context,
-) -> anyhow::Result<()> {
-    match &*data.name {
-        "ping" => PingCommand::handle(interaction, data, ctx).await,
-        "play" => PlayCommand::handle(interaction, data, ctx).await,
-        "pause" => PauseCommand::handle(interaction, data, ctx).await,
-        "resume" => ResumeCommand::handle(interaction, data, ctx).await,
-        "skip" => SkipCommand::handle(interaction, data, ctx).await,
-        "stop" => StopCommand::handle(interaction, data, ctx).await,
-        name => bail!("unknown command: {}", name),
+/// Checks that the user of `interaction` may use `command`, before its handler runs.
+async fn allowed(ctx: &Context, interaction: &Interaction, command: &str) -> anyhow::Result<()> {
+    permissions::check(ctx, interaction, command).await?;
+    voice::check(ctx, interaction, command).await
+}
+
+async fn report(ctx: &Context, interaction: &Interaction, error: anyhow::Error) {
+    if let Err(error) = report_error(ctx, interaction, error, false).await {
+        tracing::error!(?error, "failed to report error to user");
     }
 }
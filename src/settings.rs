@@ -0,0 +1,113 @@
+use std::{collections::HashMap, io::ErrorKind, path::PathBuf, sync::RwLock};
+
+use anyhow::Context as _;
+use serde::{Deserialize, Serialize};
+use twilight_model::id::{
+    Id,
+    marker::{GuildMarker, RoleMarker},
+};
+
+/// Guild preferences that survive restarts.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GuildSettings {
+    /// Volume new tracks start at, in percent.
+    pub volume: u16,
+    /// Share of listeners in percent that must vote to skip a track, or 0 to skip right away.
+    pub vote_skip: u8,
+    /// Members with this role control playback without voting.
+    pub dj_role: Option<Id<RoleMarker>>,
+    /// Whether the requester of a track may always skip it.
+    pub requester_skip: bool,
+    /// Whether a member left alone with the bot counts as a DJ.
+    pub alone_is_dj: bool,
+    /// Roles allowed to use a command, by command name. Commands missing here are open to
+    /// every member.
+    pub allowed_roles: HashMap<String, Vec<Id<RoleMarker>>>,
+    /// Whether playback pauses once every listener left, instead of the bot leaving.
+    pub pause_when_alone: bool,
+}
+
+impl GuildSettings {
+    pub fn is_dj(&self, roles: &[Id<RoleMarker>]) -> bool {
+        self.dj_role.is_some_and(|role| roles.contains(&role))
+    }
+}
+
+impl Default for GuildSettings {
+    fn default() -> Self {
+        Self {
+            volume: 100,
+            vote_skip: 0,
+            dj_role: None,
+            requester_skip: true,
+            alone_is_dj: true,
+            allowed_roles: HashMap::new(),
+            pause_when_alone: false,
+        }
+    }
+}
+
+/// Per-guild [`GuildSettings`], stored as JSON in a file.
+pub struct Settings {
+    path: PathBuf,
+    guilds: RwLock<HashMap<Id<GuildMarker>, GuildSettings>>,
+    /// Serializes writes, so an older snapshot never overwrites a newer one.
+    save: tokio::sync::Mutex<()>,
+}
+
+impl Settings {
+    /// Reads the settings stored at `path`, starting empty if the file does not exist yet.
+    pub async fn load(path: PathBuf) -> anyhow::Result<Self> {
+        let guilds = match tokio::fs::read(&path).await {
+            Ok(contents) => serde_json::from_slice(&contents)
+                .with_context(|| format!("invalid settings file {}", path.display()))?,
+            Err(error) if error.kind() == ErrorKind::NotFound => HashMap::new(),
+            Err(error) => {
+                return Err(error)
+                    .with_context(|| format!("failed to read settings file {}", path.display()));
+            }
+        };
+
+        Ok(Self {
+            path,
+            guilds: RwLock::new(guilds),
+            save: Default::default(),
+        })
+    }
+
+    pub fn get(&self, guild_id: Id<GuildMarker>) -> GuildSettings {
+        self.guilds
+            .read()
+            .unwrap()
+            .get(&guild_id)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Changes the settings of `guild_id` and writes all settings back to the file.
+    pub async fn update<T>(
+        &self,
+        guild_id: Id<GuildMarker>,
+        f: impl FnOnce(&mut GuildSettings) -> T,
+    ) -> anyhow::Result<T> {
+        let _save = self.save.lock().await;
+
+        let (result, contents) = {
+            let mut guilds = self.guilds.write().unwrap();
+            let result = f(guilds.entry(guild_id).or_default());
+            (result, serde_json::to_vec_pretty(&*guilds)?)
+        };
+
+        // Write to a temporary file first, so a crash never leaves a truncated file behind.
+        let temporary = self.path.with_extension("json.tmp");
+        tokio::fs::write(&temporary, contents)
+            .await
+            .with_context(|| format!("failed to write {}", temporary.display()))?;
+        tokio::fs::rename(&temporary, &self.path)
+            .await
+            .with_context(|| format!("failed to replace {}", self.path.display()))?;
+
+        Ok(result)
+    }
+}
@@ -9,3 +9,139 @@ pub fn to_timestamp(t: u64) -> String {
         format!("{:0>2}:{:0>2}:{:0>2}", hrs, min, sec)
     }
 }
+
+pub fn progress_bar(position: u64, duration: u64) -> String {
+    const WIDTH: u64 = 20;
+
+    let filled = match duration {
+        0 => 0,
+        duration => (position * WIDTH / duration).min(WIDTH - 1),
+    };
+
+    format!(
+        "{}🔘{}",
+        "▬".repeat(filled as usize),
+        "▬".repeat((WIDTH - 1 - filled) as usize)
+    )
+}
+
+/// Shortens `text` to at most `max` characters, marking the cut with an ellipsis.
+pub fn truncate(text: &str, max: usize) -> String {
+    if text.chars().count() <= max {
+        return text.to_owned();
+    }
+
+    let shortened: String = text.chars().take(max.saturating_sub(1)).collect();
+    format!("{}…", shortened)
+}
+
+/// A `/seek` position, absolute or relative to the current one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Seek {
+    To(u64),
+    Forward(u64),
+    Back(u64),
+}
+
+impl Seek {
+    /// Parses `90`, `1:30` or `1:02:03` as a position, and `+30` or `-15` as an offset.
+    pub fn parse(input: &str) -> Option<Self> {
+        let input = input.trim();
+        if let Some(offset) = input.strip_prefix('+') {
+            return parse_timestamp(offset).map(Seek::Forward);
+        }
+        if let Some(offset) = input.strip_prefix('-') {
+            return parse_timestamp(offset).map(Seek::Back);
+        }
+
+        parse_timestamp(input).map(Seek::To)
+    }
+
+    /// The position in seconds this seek lands on, starting from `position`.
+    pub fn target(self, position: u64) -> u64 {
+        match self {
+            Seek::To(target) => target,
+            Seek::Forward(offset) => position.saturating_add(offset),
+            Seek::Back(offset) => position.saturating_sub(offset),
+        }
+    }
+}
+
+/// Parses a number of seconds written as `ss`, `mm:ss` or `hh:mm:ss`, the inverse of
+/// [`to_timestamp`].
+///
+/// Only the leading part may exceed 59, so `90` and `90:00` are accepted but `1:90` is not.
+pub fn parse_timestamp(input: &str) -> Option<u64> {
+    let parts: Vec<&str> = input.trim().split(':').collect();
+    if parts.len() > 3 {
+        return None;
+    }
+
+    let mut seconds = 0u64;
+    for (index, part) in parts.iter().enumerate() {
+        if part.is_empty() || !part.bytes().all(|byte| byte.is_ascii_digit()) {
+            return None;
+        }
+
+        let value: u64 = part.parse().ok()?;
+        if index > 0 && value >= 60 {
+            return None;
+        }
+        seconds = seconds.checked_mul(60)?.checked_add(value)?;
+    }
+
+    Some(seconds)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_timestamps() {
+        assert_eq!(parse_timestamp("90"), Some(90));
+        assert_eq!(parse_timestamp("1:30"), Some(90));
+        assert_eq!(parse_timestamp("01:30"), Some(90));
+        assert_eq!(parse_timestamp("1:02:03"), Some(3723));
+        assert_eq!(parse_timestamp("0"), Some(0));
+    }
+
+    #[test]
+    fn rejects_invalid_timestamps() {
+        assert_eq!(parse_timestamp(""), None);
+        assert_eq!(parse_timestamp("1:"), None);
+        assert_eq!(parse_timestamp(":30"), None);
+        assert_eq!(parse_timestamp("1:90"), None);
+        assert_eq!(parse_timestamp("1:2:3:4"), None);
+        assert_eq!(parse_timestamp("abc"), None);
+        assert_eq!(parse_timestamp("+5"), None);
+        assert_eq!(parse_timestamp("1.5"), None);
+    }
+
+    #[test]
+    fn parse_is_inverse_of_to_timestamp() {
+        for seconds in [0, 5, 59, 60, 90, 3599, 3600, 3723, 86399] {
+            assert_eq!(parse_timestamp(&to_timestamp(seconds)), Some(seconds));
+        }
+    }
+
+    #[test]
+    fn parses_seeks() {
+        assert_eq!(Seek::parse("90"), Some(Seek::To(90)));
+        assert_eq!(Seek::parse("1:30"), Some(Seek::To(90)));
+        assert_eq!(Seek::parse("1:02:03"), Some(Seek::To(3723)));
+        assert_eq!(Seek::parse("+30"), Some(Seek::Forward(30)));
+        assert_eq!(Seek::parse("-15"), Some(Seek::Back(15)));
+        assert_eq!(Seek::parse("-1:00"), Some(Seek::Back(60)));
+        assert_eq!(Seek::parse("+-5"), None);
+        assert_eq!(Seek::parse("soon"), None);
+    }
+
+    #[test]
+    fn seek_targets() {
+        assert_eq!(Seek::To(90).target(10), 90);
+        assert_eq!(Seek::Forward(30).target(10), 40);
+        assert_eq!(Seek::Back(15).target(40), 25);
+        assert_eq!(Seek::Back(15).target(10), 0);
+    }
+}